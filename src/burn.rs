@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+use crate::parallelism::{Buffer, Executor, ExecutorError, RunLength};
+use crate::interrupt::is_interrupted;
+
+pub const BURN_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+// Samples the peak throughput is taken from, the first minute
+const PEAK_SAMPLES: usize = 60;
+// Samples averaged when looking for a drop, so a single slow second doesn't count as throttling
+const THROTTLE_WINDOW: usize = 5;
+
+// Keeps the executor busy for the whole duration, printing the throughput of each sample interval as it goes.
+// Returns the buffer reached and the iterations per second of every interval, the run stopping early if interrupted.
+pub fn burn<T, TConf>(executor: &dyn Executor<T, TConf>, duration: Duration, mut buffer: Buffer<T>, conf: TConf) -> Result<(Buffer<T>, Vec<f32>), ExecutorError>
+    where
+        T: Clone,
+        TConf: Clone,
+{
+    let mut samples = Vec::new();
+    let start = Instant::now();
+    while start.elapsed() < duration && !is_interrupted() {
+        let interval = Duration::min(BURN_SAMPLE_INTERVAL, duration - start.elapsed());
+        let now = Instant::now();
+        let completed;
+        (buffer, completed) = executor.advance(RunLength::Duration(interval), buffer, TConf::clone(&conf))?;
+        // An interval cut short isn't a sample of the sustained throughput
+        if is_interrupted() {
+            break;
+        }
+        let iter_per_sec = completed as f32 / f32::max(now.elapsed().as_secs_f32(), f32::EPSILON);
+        println!("{:>6.1}s: {:.1} iterations per second", start.elapsed().as_secs_f32(), iter_per_sec);
+        samples.push(iter_per_sec);
+    }
+    Ok((buffer, samples))
+}
+
+pub struct ThrottleReport {
+    // Highest sample of the first minute
+    pub peak: f32,
+    // Lowest average of THROTTLE_WINDOW consecutive samples from the peak on
+    pub lowest: f32,
+    // Sample at which the average first dropped more than the threshold below the peak
+    pub throttled_at: Option<usize>,
+}
+
+// Compares the throughput after the first minute's peak to it, the samples before the peak being warm-up.
+// There's nothing to compare with fewer samples than a window.
+pub fn detect_throttling(samples: &[f32], threshold_percent: f32) -> Option<ThrottleReport> {
+    let (peak_index, peak) = samples
+        .iter()
+        .take(PEAK_SAMPLES)
+        .copied()
+        .enumerate()
+        .fold(None, |best: Option<(usize, f32)>, (index, sample)| match best {
+            Some((_, best_sample)) if best_sample >= sample => best,
+            _ => Some((index, sample)),
+        })?;
+    let averages: Vec<f32> = samples[peak_index..]
+        .windows(THROTTLE_WINDOW)
+        .map(|window| window.iter().sum::<f32>() / THROTTLE_WINDOW as f32)
+        .collect();
+    let lowest = averages.iter().copied().reduce(f32::min)?;
+    let limit = peak * (1_f32 - threshold_percent / 100_f32);
+    Some(ThrottleReport {
+        peak,
+        lowest,
+        throttled_at: averages
+            .iter()
+            .position(|average| *average < limit)
+            .map(|window_start| peak_index + window_start + THROTTLE_WINDOW - 1),
+    })
+}
+
+pub fn print_burn_summary(samples: &[f32], threshold_percent: f32) {
+    if samples.is_empty() {
+        println!("The burn stopped before taking any sample");
+        return;
+    }
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    println!("Mean throughput: {:.1} iterations per second over {} samples", mean, samples.len());
+    let report = match detect_throttling(samples, threshold_percent) {
+        Some(report) => report,
+        None => {
+            println!("Too few samples to detect throttling, at least {} are needed from the first minute's peak on", THROTTLE_WINDOW);
+            return;
+        },
+    };
+    println!(
+        "First minute peak: {:.1} iterations per second, lowest {}s average: {:.1} ({:.1}% below the peak)",
+        report.peak,
+        THROTTLE_WINDOW,
+        report.lowest,
+        100_f32 * (1_f32 - report.lowest / report.peak),
+    );
+    match report.throttled_at {
+        Some(sample) => println!(
+            "Throttling detected: the {}s average fell more than {}% below the peak after {:?}",
+            THROTTLE_WINDOW,
+            threshold_percent,
+            BURN_SAMPLE_INTERVAL * (sample as u32 + 1),
+        ),
+        None => println!("No throttling detected, the {}s average stayed within {}% of the peak", THROTTLE_WINDOW, threshold_percent),
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::interrupt::is_interrupted;
+use crate::parallelism::{Jobber, Buffer, Executor, ExecutorError, RunLength, check_buffers};
+use crate::jobbers::gol::{GolCell, GameOfLifeJobber};
+
+type NodeId = u32;
+
+const DEAD_LEAF: NodeId = 0;
+const ALIVE_LEAF: NodeId = 1;
+// Node count past which the nodes the root isn't made of are dropped, along with the memoized successors
+const GARBAGE_COLLECTION_THRESHOLD: usize = 1 << 21;
+// Largest jump of a run with a duration, keeping the generation count and the population of growing patterns far from overflowing
+const MAX_DURATION_STEP_LOG2: u8 = 32;
+
+// A square of 2^level by 2^level cells, leaves (level 0) being single cells
+#[derive(Clone, Copy)]
+struct Node {
+    level: u8,
+    // North west, north east, south west, south east
+    children: [NodeId; 4],
+    population: u64,
+}
+
+// Quadtree Game of Life with memoized node successors, where identical squares are stored only once.
+// The universe is unbounded, unlike the buffers of the dense executors which are surrounded by dead cells.
+pub struct HashLife {
+    nodes: Vec<Node>,
+    interned: HashMap<[NodeId; 4], NodeId>,
+    // (node, log2 of the generations advanced) to the node's center after that many generations
+    successors: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    // Position of the root's top left cell in universe coordinates
+    origin: (i64, i64),
+    generation: u64,
+    // Node count of the next garbage collection, raised when most nodes survive one
+    collection_threshold: usize,
+}
+
+impl HashLife {
+    pub fn new() -> Self {
+        let leaf = |population| Node { level: 0, children: [DEAD_LEAF; 4], population };
+        let mut hashlife = Self {
+            nodes: vec![leaf(0), leaf(1)],
+            interned: HashMap::new(),
+            successors: HashMap::new(),
+            empty: vec![DEAD_LEAF],
+            root: DEAD_LEAF,
+            origin: (0, 0),
+            generation: 0,
+            collection_threshold: GARBAGE_COLLECTION_THRESHOLD,
+        };
+        hashlife.root = hashlife.empty(2);
+        hashlife
+    }
+
+    // Builds a quadtree just large enough to hold the buffer, whose top left cell lands at the origin, padding the rest with empty nodes
+    pub fn from_buffer(buffer: &Buffer<GolCell>) -> Self {
+        let mut hashlife = Self::new();
+        let (width, height) = buffer.dims_2d();
+        let mut level = 2;
+        while (1 << level) < usize::max(width, height) {
+            level += 1;
+        }
+        hashlife.root = hashlife.build(buffer, (0, 0), level);
+        hashlife
+    }
+
+    fn build(&mut self, buffer: &Buffer<GolCell>, pos: (usize, usize), level: u8) -> NodeId {
+        if pos.0 >= buffer.dims.0 || pos.1 >= buffer.dims.1 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return match buffer.at_2d_unchecked(pos).is_alive() {
+                true => ALIVE_LEAF,
+                false => DEAD_LEAF,
+            };
+        }
+        let half = 1 << (level - 1);
+        let nw = self.build(buffer, pos, level - 1);
+        let ne = self.build(buffer, (pos.0 + half, pos.1), level - 1);
+        let sw = self.build(buffer, (pos.0, pos.1 + half), level - 1);
+        let se = self.build(buffer, (pos.0 + half, pos.1 + half), level - 1);
+        self.join([nw, ne, sw, se])
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(id) = self.interned.get(&children) {
+            return *id;
+        }
+        let level = self.node(children[0]).level + 1;
+        let population = children.iter().map(|child| self.node(*child).population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { level, children, population });
+        self.interned.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let child = *self.empty.last().unwrap();
+            let node = self.join([child; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    // The node one level down made of the innermost grandchildren
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).children;
+        self.join([
+            self.node(nw).children[3],
+            self.node(ne).children[2],
+            self.node(sw).children[1],
+            self.node(se).children[0],
+        ])
+    }
+
+    // Doubles the universe's size around the root, keeping the root centered
+    fn expand(&mut self) {
+        let root = self.node(self.root);
+        let empty = self.empty(root.level - 1);
+        let [nw, ne, sw, se] = root.children;
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let half = 1_i64 << (root.level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+
+    // Level 2 base case, computed cell by cell with the reference jobber
+    fn successor_4x4(&mut self, id: NodeId) -> NodeId {
+        let mut buffer = Buffer::from_value_2d((4, 4), GolCell::Dead);
+        for (index, cell) in buffer.data.iter_mut().enumerate() {
+            let (x, y) = (index % 4, index / 4);
+            let child = self.node(id).children[(x / 2) + (y / 2) * 2];
+            let leaf = self.node(child).children[(x % 2) + (y % 2) * 2];
+            *cell = (leaf == ALIVE_LEAF).into();
+        }
+        let mut next = [DEAD_LEAF; 4];
+        for (quadrant, (x, y)) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter().enumerate() {
+            if GameOfLifeJobber::process_job(&buffer, x + y * 4, &()).is_alive() {
+                next[quadrant] = ALIVE_LEAF;
+            }
+        }
+        self.join(next)
+    }
+
+    // The center half of a node (level >= 2), advanced 2^step_log2 generations, step_log2 being at most level - 2
+    fn successor(&mut self, id: NodeId, step_log2: u8) -> NodeId {
+        let node = self.node(id);
+        let step_log2 = u8::min(step_log2, node.level - 2);
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(result) = self.successors.get(&(id, step_log2)) {
+            return *result;
+        }
+
+        let result = if node.level == 2 {
+            self.successor_4x4(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.node(nw).children;
+            let [ne_nw, _, ne_sw, ne_se] = self.node(ne).children;
+            let [sw_nw, sw_ne, _, sw_se] = self.node(sw).children;
+            let [se_nw, se_ne, se_sw, _] = self.node(se).children;
+            // Nine overlapping squares one level down, in a 3x3 grid
+            let grid = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+            let mut advanced = [DEAD_LEAF; 9];
+            for (slot, square) in advanced.iter_mut().zip(grid.iter()) {
+                *slot = self.successor(*square, step_log2);
+            }
+            let quadrants = [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]];
+            let mut children = [DEAD_LEAF; 4];
+            for (child, quadrant) in children.iter_mut().zip(quadrants.iter()) {
+                let joined = self.join([advanced[quadrant[0]], advanced[quadrant[1]], advanced[quadrant[2]], advanced[quadrant[3]]]);
+                // A full step needs a second half step, a smaller one just needs realigning on the center
+                *child = match step_log2 == node.level - 2 {
+                    true => self.successor(joined, step_log2),
+                    false => self.center(joined),
+                };
+            }
+            self.join(children)
+        };
+        self.successors.insert((id, step_log2), result);
+        result
+    }
+
+    pub fn advance_pow2(&mut self, step_log2: u8) {
+        // The pattern must stay within the root's center half, with enough room to grow for the step
+        loop {
+            let root = self.node(self.root);
+            let center = self.center(self.root);
+            if root.level >= step_log2 + 2 && self.node(center).population == root.population {
+                break;
+            }
+            self.expand();
+        }
+        self.expand();
+        let level = self.node(self.root).level;
+        self.root = self.successor(self.root, step_log2);
+        let quarter = 1_i64 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.generation += 1 << step_log2;
+        if self.nodes.len() > self.collection_threshold {
+            self.collect_garbage();
+        }
+    }
+
+    // Rebuilds the tables from the nodes the root is made of, forgetting every successor
+    fn collect_garbage(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = nodes[..=(ALIVE_LEAF as usize)].to_vec();
+        self.interned.clear();
+        self.successors.clear();
+        self.empty = vec![DEAD_LEAF];
+        let mut copied = HashMap::new();
+        self.root = self.copy_node(&nodes, self.root, &mut copied);
+        self.collection_threshold = usize::max(GARBAGE_COLLECTION_THRESHOLD, 2 * self.nodes.len());
+    }
+
+    fn copy_node(&mut self, nodes: &[Node], id: NodeId, copied: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id <= ALIVE_LEAF {
+            return id;
+        }
+        if let Some(copy) = copied.get(&id) {
+            return *copy;
+        }
+        let mut children = nodes[id as usize].children;
+        for child in children.iter_mut() {
+            *child = self.copy_node(nodes, *child, copied);
+        }
+        let copy = self.join(children);
+        copied.insert(id, copy);
+        copy
+    }
+
+    // Any count of generations, as a sum of power of two jumps
+    pub fn advance(&mut self, generations: u64) {
+        for step_log2 in 0..64 {
+            if generations & (1 << step_log2) != 0 {
+                self.advance_pow2(step_log2);
+            }
+        }
+    }
+
+    // Cells of a window of the universe, its top left cell being at pos
+    pub fn to_buffer(&self, pos: (i64, i64), dimensions: (usize, usize)) -> Buffer<GolCell> {
+        let mut buffer = Buffer::from_value_2d(dimensions, GolCell::Dead);
+        self.write_window(self.root, self.origin, pos, &mut buffer);
+        buffer
+    }
+
+    fn write_window(&self, id: NodeId, node_pos: (i64, i64), window_pos: (i64, i64), buffer: &mut Buffer<GolCell>) {
+        let node = self.node(id);
+        let size = 1_i64 << node.level;
+        let (width, height) = (buffer.dims.0 as i64, buffer.dims.1 as i64);
+        let (x, y) = (node_pos.0 - window_pos.0, node_pos.1 - window_pos.1);
+        if node.population == 0 || x >= width || y >= height || x + size <= 0 || y + size <= 0 {
+            return;
+        }
+        if node.level == 0 {
+            buffer.data[(x + y * width) as usize] = GolCell::Alive;
+            return;
+        }
+        let half = size / 2;
+        for (quadrant, child) in node.children.iter().enumerate() {
+            let child_pos = (node_pos.0 + (quadrant as i64 % 2) * half, node_pos.1 + (quadrant as i64 / 2) * half);
+            self.write_window(*child, child_pos, window_pos, buffer);
+        }
+    }
+}
+
+// Exposes HashLife through the Executor interface, on the window of the universe covered by the buffers.
+// Cells leaving the window keep evolving, unlike with the dense executors.
+pub struct ExecutorHashLife { }
+
+impl ExecutorHashLife {
+    pub fn new() -> Self {
+        Self { }
+    }
+}
+
+impl Executor<GolCell, ()> for ExecutorHashLife {
+    fn compute(&self, in_buffer: Buffer<GolCell>, out_buffer: &mut [GolCell], _conf: ()) -> Result<Buffer<GolCell>, ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
+        let mut hashlife = HashLife::from_buffer(&in_buffer);
+        hashlife.advance(1);
+        out_buffer.copy_from_slice(&hashlife.to_buffer((0, 0), in_buffer.dims_2d()).data);
+        Ok(in_buffer)
+    }
+
+    // A jump can't be stopped halfway, so a duration is run in jumps doubling while the next one likely fits in the time left,
+    // and halving once the last one took longer than what's left
+    fn compute_run(&self, length: RunLength, buffer: Buffer<GolCell>, _conf: ()) -> Result<(Buffer<GolCell>, usize), ExecutorError> {
+        check_buffers(&buffer, &buffer.data)?;
+        let mut hashlife = HashLife::from_buffer(&buffer);
+        let now = Instant::now();
+        match length {
+            RunLength::Iterations(iterations) => hashlife.advance(iterations as u64),
+            RunLength::Duration(duration) => {
+                let mut step_log2 = 0;
+                while now.elapsed() < duration && !is_interrupted() {
+                    let jump_start = Instant::now();
+                    hashlife.advance_pow2(step_log2);
+                    let jump_time = jump_start.elapsed();
+                    let remaining = duration.saturating_sub(now.elapsed());
+                    if jump_time * 4 < remaining {
+                        step_log2 = u8::min(step_log2 + 1, MAX_DURATION_STEP_LOG2);
+                    } else if jump_time > remaining {
+                        step_log2 = step_log2.saturating_sub(1);
+                    }
+                }
+                match is_interrupted() {
+                    true => println!("Interrupted after {} generations", hashlife.generation()),
+                    false => println!("Completed {} generations in the {:?} budget", hashlife.generation(), duration),
+                }
+            },
+        }
+        let elapsed = now.elapsed().as_secs_f32();
+        println!(
+            "Time elapsed: {}s, {} generations per second, generation {}, population {}, {} nodes",
+            elapsed,
+            hashlife.generation() as f32 / f32::max(elapsed, f32::EPSILON),
+            hashlife.generation(),
+            hashlife.population(),
+            hashlife.node_count(),
+        );
+        Ok((hashlife.to_buffer((0, 0), buffer.dims_2d()), hashlife.generation() as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobbers::gol::GameOfLifeJobber;
+    use crate::parallelism::Jobber;
+
+    // Large enough for the R-pentomino and its first glider to stay clear of the edges for 100 generations
+    const SIZE: usize = 128;
+
+    fn r_pentomino() -> Buffer<GolCell> {
+        let mut buffer = Buffer::from_value_2d((SIZE, SIZE), GolCell::Dead);
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)] {
+            buffer.data[(SIZE / 2 + x) + (SIZE / 2 + y) * SIZE] = GolCell::Alive;
+        }
+        buffer
+    }
+
+    fn dense_advance(mut buffer: Buffer<GolCell>, generations: u64) -> Buffer<GolCell> {
+        for _ in 0..generations {
+            let mut out = Buffer::clone(&buffer);
+            GameOfLifeJobber::process_span(&buffer, 0..buffer.len(), &(), &mut out.data);
+            buffer = out;
+        }
+        buffer
+    }
+
+    fn alive_cells(buffer: &Buffer<GolCell>) -> Vec<(usize, usize)> {
+        (0..buffer.len()).filter(|index| buffer.data[*index].is_alive()).map(|index| buffer.index_to_pos_2d(index)).collect()
+    }
+
+    #[test]
+    fn advance_pow2_matches_dense_reference() {
+        for step_log2 in 0..=6 {
+            let mut hashlife = HashLife::from_buffer(&r_pentomino());
+            hashlife.advance_pow2(step_log2);
+            let expected = dense_advance(r_pentomino(), 1 << step_log2);
+            assert_eq!(hashlife.generation(), 1 << step_log2);
+            assert_eq!(alive_cells(&hashlife.to_buffer((0, 0), (SIZE, SIZE))), alive_cells(&expected), "step 2^{}", step_log2);
+        }
+    }
+
+    #[test]
+    fn advance_matches_dense_reference() {
+        let mut hashlife = HashLife::from_buffer(&r_pentomino());
+        let mut expected = r_pentomino();
+        for generations in [1, 6, 37, 56] {
+            hashlife.advance(generations);
+            expected = dense_advance(expected, generations);
+            assert_eq!(hashlife.population(), alive_cells(&expected).len() as u64);
+            assert_eq!(alive_cells(&hashlife.to_buffer((0, 0), (SIZE, SIZE))), alive_cells(&expected), "generation {}", hashlife.generation());
+        }
+    }
+
+    #[test]
+    fn garbage_collection_keeps_the_pattern() {
+        let mut hashlife = HashLife::from_buffer(&r_pentomino());
+        let mut expected = r_pentomino();
+        for _ in 0..20 {
+            hashlife.collection_threshold = 0;
+            hashlife.advance_pow2(2);
+            expected = dense_advance(expected, 4);
+        }
+        assert_eq!(alive_cells(&hashlife.to_buffer((0, 0), (SIZE, SIZE))), alive_cells(&expected));
+    }
+}
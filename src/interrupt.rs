@@ -0,0 +1,60 @@
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use signal_hook::consts::SIGINT;
+use signal_hook::{flag, low_level};
+
+// Exit code of a process killed by SIGINT, used when Ctrl-C is pressed a second time
+const SIGINT_EXIT_CODE: i32 = 130;
+
+// Shows the cursor again and ends the visualization's last frame
+const RESTORE_TERMINAL: &[u8] = b"\x1b[?25h\n";
+
+static CURSOR_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+fn interrupt_flag() -> &'static Arc<AtomicBool> {
+    static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    INTERRUPTED.get_or_init(|| Arc::new(AtomicBool::new(false)))
+}
+
+// Whoever hides the cursor tells it here, so exiting on a second Ctrl-C, which skips destructors, can show it again
+pub fn set_cursor_hidden(hidden: bool) {
+    CURSOR_HIDDEN.store(hidden, Ordering::SeqCst);
+}
+
+// Only does what's allowed in a signal handler
+#[cfg(target_os = "linux")]
+fn restore_terminal() {
+    if CURSOR_HIDDEN.load(Ordering::SeqCst) {
+        // SAFETY: write is async-signal-safe, and the pointer and length come from the same static slice
+        unsafe {
+            libc::write(libc::STDOUT_FILENO, RESTORE_TERMINAL.as_ptr() as *const libc::c_void, RESTORE_TERMINAL.len());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn restore_terminal() {}
+
+// The first Ctrl-C only raises the flag, letting the run stop after the current generation and report what it did.
+// A second one exits right away, in case the run doesn't check the flag, restoring the terminal first.
+pub fn install_interrupt_handler() -> Result<(), String> {
+    let interrupted = interrupt_flag();
+    let shutdown_flag = Arc::clone(interrupted);
+    // Registered first, so it only sees the flag raised by an earlier Ctrl-C.
+    // SAFETY: the action only loads atomics, writes to stdout and exits, all async-signal-safe
+    unsafe {
+        low_level::register(SIGINT, move || {
+            if shutdown_flag.load(Ordering::SeqCst) {
+                restore_terminal();
+                low_level::exit(SIGINT_EXIT_CODE);
+            }
+        })
+    }
+        .and_then(|_| flag::register(SIGINT, Arc::clone(interrupted)))
+        .map_err(|error| format!("Unable to install the Ctrl-C handler: {}", error))?;
+    Ok(())
+}
+
+pub fn is_interrupted() -> bool {
+    interrupt_flag().load(Ordering::Relaxed)
+}
@@ -1,11 +1,16 @@
 use std::fmt::Debug;
-use crate::parallelism::{Jobber, Buffer, Executor};
+use crate::parallelism::{Jobber, Buffer, Executor, ExecutorError, check_buffers};
 use std::io::{Write, stdout};
+use crossterm::{cursor, execute};
+use crate::interrupt::set_cursor_hidden;
+use std::ops::Range;
 
+// The SIMD jobber relies on cells being single bytes holding their alive neighbor contribution
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
 pub enum GolCell {
-    Alive,
-    Dead,
+    Alive = 1,
+    Dead = 0,
 }
 
 impl GolCell {
@@ -43,7 +48,7 @@ impl Into<char> for GolCell {
 
 pub struct GameOfLifeJobber { }
 
-const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+pub const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
     (-1,  1),
     ( 0,  1),
     ( 1,  1),
@@ -81,9 +86,66 @@ impl GameOfLifeJobber {
         return neighbor_count;
         */
     }
+
+    pub fn next_state(cell: GolCell, neighbor_count: usize) -> GolCell {
+        ((neighbor_count == 3) || (neighbor_count == 2 && cell.is_alive())).into()
+    }
+}
+
+// The part of a row a span covers, with the rows above and below it, None beyond the buffer's top and bottom edges
+pub struct SpanRow<'a> {
+    pub above: Option<&'a [GolCell]>,
+    pub current: &'a [GolCell],
+    pub below: Option<&'a [GolCell]>,
+    pub cells: Range<usize>,
+}
+
+// Splits a span, which may cover several rows, in the part of each row it covers, and calls the row kernel
+// with each of them and the matching part of out_span. The row-major jobbers only differ in their kernels.
+pub fn for_each_span_row<F>(buffer: &Buffer<GolCell>, indices: Range<usize>, out_span: &mut [GolCell], mut row_kernel: F)
+    where F: FnMut(SpanRow, &mut [GolCell])
+{
+    let (width, height) = buffer.dims_2d();
+    let row_at = |row_y: usize| &buffer.data[(row_y * width)..((row_y + 1) * width)];
+    let mut written = 0;
+    while written < indices.len() {
+        let (start_x, y) = buffer.index_to_pos_2d(indices.start + written);
+        let end_x = usize::min(width, start_x + indices.len() - written);
+        let row = SpanRow {
+            above: if y > 0 { Some(row_at(y - 1)) } else { None },
+            current: row_at(y),
+            below: if y + 1 < height { Some(row_at(y + 1)) } else { None },
+            cells: start_x..end_x,
+        };
+        row_kernel(row, &mut out_span[written..(written + end_x - start_x)]);
+        written += end_x - start_x;
+    }
 }
 
 impl Jobber<GolCell, ()> for GameOfLifeJobber {
+    // Slides a window of three column sums along each row instead of reading eight neighbors per cell
+    fn process_span(buffer: &Buffer<GolCell>, indices: Range<usize>, _conf: &(), out_span: &mut [GolCell]) {
+        for_each_span_row(buffer, indices, out_span, |row, out_row| {
+            let width = row.current.len();
+            let column_sum = |x: usize| -> usize {
+                row.above.map_or(0, |above| above[x].is_alive() as usize)
+                    + row.current[x].is_alive() as usize
+                    + row.below.map_or(0, |below| below[x].is_alive() as usize)
+            };
+
+            let mut left = if row.cells.start > 0 { column_sum(row.cells.start - 1) } else { 0 };
+            let mut middle = column_sum(row.cells.start);
+            for (out_cell, x) in out_row.iter_mut().zip(row.cells.clone()) {
+                let cell = row.current[x];
+                let right = if x + 1 < width { column_sum(x + 1) } else { 0 };
+                let neighbor_count = left + middle + right - cell.is_alive() as usize;
+                *out_cell = GameOfLifeJobber::next_state(cell, neighbor_count);
+                left = middle;
+                middle = right;
+            }
+        });
+    }
+
     fn process_job(buffer: &Buffer<GolCell>, index: usize, _conf: &()) -> GolCell {
         let cell_pos = buffer.index_to_pos_2d(index);
         let cell = buffer.data[index];
@@ -96,7 +158,7 @@ impl Jobber<GolCell, ()> for GameOfLifeJobber {
             GolCell::Dead => neighbor_count == 3,
         }.into();
         */
-        return ((neighbor_count == 3) || (neighbor_count == 2 && cell.is_alive())).into();
+        return GameOfLifeJobber::next_state(cell, neighbor_count);
     }
 }
 
@@ -107,23 +169,34 @@ pub struct ExecutorGolVis {
 }
 
 impl ExecutorGolVis {
+    // The cursor is hidden while frames are drawn
     pub fn new(target_framerate: usize) -> Self {
+        let _ = execute!(stdout(), cursor::Hide);
+        set_cursor_hidden(true);
         Self {
             frame_interval: 1_f32 / target_framerate as f32,
         }
     }
 }
 
+// Restores the terminal however the visualization ended, e.g. interrupted, with the prompt on a line of its own
+impl Drop for ExecutorGolVis {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), cursor::Show);
+        set_cursor_hidden(false);
+        println!();
+    }
+}
+
 impl Executor<GolCell, ()> for ExecutorGolVis
 {
-    fn compute(&self, in_buffer: Buffer<GolCell>, out_buffer: &mut [GolCell], conf: ()) -> Buffer<GolCell> {
+    fn compute(&self, in_buffer: Buffer<GolCell>, out_buffer: &mut [GolCell], conf: ()) -> Result<Buffer<GolCell>, ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
         // Very bad way to do this since it adds the processing and printing overhead to the loop
         // but it avoids interior mutability of the Executor; but maybe it should be &mut anyway
         std::thread::sleep(std::time::Duration::from_secs_f32(self.frame_interval));
 
-        for index in 0..(in_buffer.len()) {
-            out_buffer[index] = GameOfLifeJobber::process_job(&in_buffer, index, &conf);
-        }
+        GameOfLifeJobber::process_span(&in_buffer, 0..(in_buffer.len()), &conf, out_buffer);
 
         let mut stdout = stdout();
         let output: String = out_buffer
@@ -137,7 +210,7 @@ impl Executor<GolCell, ()> for ExecutorGolVis
             .flatten()
             .collect()
             ;
-        stdout.write_all(output.trim().as_bytes()).expect("Error writing to console");
-        return in_buffer;
+        stdout.write_all(output.trim().as_bytes())?;
+        return Ok(in_buffer);
     }
 }
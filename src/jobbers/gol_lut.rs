@@ -0,0 +1,66 @@
+use std::ops::Range;
+use std::sync::OnceLock;
+use crate::parallelism::{Jobber, Buffer};
+use crate::jobbers::gol::{GolCell, GameOfLifeJobber, for_each_span_row};
+
+// A neighborhood index holds three 3 bit columns, left column in the high bits,
+// each column having its top cell in the high bit, so the center cell is bit 4
+const CENTER_BIT: usize = 4;
+const NEIGHBORHOOD_MASK: usize = 0b111_111_111;
+
+static LOOKUP_TABLE: OnceLock<[GolCell; 512]> = OnceLock::new();
+
+// Game of Life evaluated through a table of all 512 possible 3x3 neighborhoods,
+// independent from GameOfLifeJobber's neighbor counting apart from sharing its rule
+pub struct LookupTableGolJobber { }
+
+impl LookupTableGolJobber {
+    pub fn lookup_table() -> &'static [GolCell; 512] {
+        LOOKUP_TABLE.get_or_init(|| {
+            let mut table = [GolCell::Dead; 512];
+            for (neighborhood, next) in table.iter_mut().enumerate() {
+                let cell = GolCell::from((neighborhood >> CENTER_BIT) & 1 == 1);
+                let neighbor_count = neighborhood.count_ones() as usize - cell.is_alive() as usize;
+                *next = GameOfLifeJobber::next_state(cell, neighbor_count);
+            }
+            table
+        })
+    }
+
+    fn column_bits(buffer: &Buffer<GolCell>, x: i32, y: i32) -> usize {
+        (-1..=1).fold(0, |bits, offset_y| {
+            let alive = buffer.at_2d_i32((x, y + offset_y)).is_some_and(|cell| cell.is_alive());
+            (bits << 1) | alive as usize
+        })
+    }
+}
+
+impl Jobber<GolCell, ()> for LookupTableGolJobber {
+    // Shifts the neighborhood index one column to the left per cell, only reading the new right column
+    fn process_span(buffer: &Buffer<GolCell>, indices: Range<usize>, _conf: &(), out_span: &mut [GolCell]) {
+        let table = LookupTableGolJobber::lookup_table();
+        for_each_span_row(buffer, indices, out_span, |row, out_row| {
+            let width = row.current.len();
+            let column_bits = |x: usize| -> usize {
+                if x >= width { return 0 }
+                (row.above.is_some_and(|above| above[x].is_alive()) as usize) << 2
+                    | (row.current[x].is_alive() as usize) << 1
+                    | row.below.is_some_and(|below| below[x].is_alive()) as usize
+            };
+
+            let left = if row.cells.start > 0 { column_bits(row.cells.start - 1) } else { 0 };
+            let mut neighborhood = (left << 3) | column_bits(row.cells.start);
+            for (out_cell, x) in out_row.iter_mut().zip(row.cells.clone()) {
+                neighborhood = ((neighborhood << 3) | column_bits(x + 1)) & NEIGHBORHOOD_MASK;
+                *out_cell = table[neighborhood];
+            }
+        });
+    }
+
+    fn process_job(buffer: &Buffer<GolCell>, index: usize, _conf: &()) -> GolCell {
+        let (x, y) = buffer.index_to_pos_2d(index);
+        let (x, y) = (x as i32, y as i32);
+        let neighborhood = (-1..=1).fold(0, |bits, offset_x| (bits << 3) | LookupTableGolJobber::column_bits(buffer, x + offset_x, y));
+        LookupTableGolJobber::lookup_table()[neighborhood]
+    }
+}
@@ -0,0 +1,57 @@
+use std::ops::Range;
+use crate::parallelism::{Jobber, Buffer, JobRegion, WorkDecomposition};
+use crate::morton::MortonLayout;
+use super::gol::{GolCell, GameOfLifeJobber, NEIGHBOR_OFFSETS};
+
+// Game of Life on Morton ordered buffers, the layout giving the grid's dimensions
+pub struct MortonGolJobber { }
+
+impl Jobber<GolCell, MortonLayout> for MortonGolJobber {
+    // Interior cells, the vast majority on large grids, skip the bounds checks
+    fn process_span(buffer: &Buffer<GolCell>, indices: Range<usize>, layout: &MortonLayout, out_span: &mut [GolCell]) {
+        for (out, index) in out_span.iter_mut().zip(indices) {
+            let pos = layout.index_to_pos(index);
+            *out = match layout.is_interior(pos) {
+                true => {
+                    let neighbor_count = NEIGHBOR_OFFSETS
+                        .iter()
+                        .filter(|offset| buffer.data[MortonLayout::offset_index(index, **offset)].is_alive())
+                        .count();
+                    GameOfLifeJobber::next_state(buffer.data[index], neighbor_count)
+                },
+                false => Self::process_job(buffer, index, layout),
+            };
+        }
+    }
+
+    // Aligned tiles whose top left cell is outside the grid are entirely holes, which stay dead
+    fn regions(decomposition: &WorkDecomposition, dims: (usize, usize), layout: &MortonLayout) -> Vec<JobRegion> {
+        decomposition
+            .regions(dims)
+            .into_iter()
+            .filter(|region| layout.contains(layout.index_to_pos(region.offset())))
+            .collect()
+    }
+
+    // Neighbors aren't where bounding boxes and halos of row-major regions would put them
+    fn supports_region_generations() -> bool {
+        false
+    }
+
+    fn supports_inactive_skipping() -> bool {
+        false
+    }
+
+    fn process_job(buffer: &Buffer<GolCell>, index: usize, layout: &MortonLayout) -> GolCell {
+        let pos = layout.index_to_pos(index);
+        if !layout.contains(pos) {
+            return GolCell::Dead;
+        }
+        let neighbor_count = NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(|offset| layout.neighbor(index, pos, *offset))
+            .filter(|neighbor| buffer.data[*neighbor].is_alive())
+            .count();
+        GameOfLifeJobber::next_state(buffer.data[index], neighbor_count)
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::Range;
+use crate::parallelism::{Jobber, Buffer};
+use crate::padded::PaddedLayout;
+use super::gol::{GolCell, GameOfLifeJobber};
+
+// Same sliding column sums as the reference jobber, but the ghost border removes every edge case
+pub struct PaddedGolJobber { }
+
+impl PaddedGolJobber {
+    // Cells of a single row inside the grid, whose neighbors are all in the buffer
+    fn process_inside(buffer: &Buffer<GolCell>, indices: Range<usize>, stride: usize, out_span: &mut [GolCell]) {
+        let column_sum = |index: usize| {
+            buffer.data[index - stride] as usize + buffer.data[index] as usize + buffer.data[index + stride] as usize
+        };
+        let mut left = column_sum(indices.start - 1);
+        let mut middle = column_sum(indices.start);
+        for (out_cell, index) in out_span.iter_mut().zip(indices) {
+            let right = column_sum(index + 1);
+            let cell = buffer.data[index];
+            *out_cell = GameOfLifeJobber::next_state(cell, left + middle + right - cell as usize);
+            left = middle;
+            middle = right;
+        }
+    }
+}
+
+impl Jobber<GolCell, PaddedLayout> for PaddedGolJobber {
+    // Each physical row of the span is split in its ghost cells and the cells inside the grid
+    fn process_span(buffer: &Buffer<GolCell>, indices: Range<usize>, layout: &PaddedLayout, out_span: &mut [GolCell]) {
+        let stride = layout.stride();
+        let mut index = indices.start;
+        while index < indices.end {
+            let row_end = usize::min((index / stride + 1) * stride, indices.end);
+            let y = layout.index_to_pos(index).1;
+            let (inside_start, inside_end) = match y >= 0 && y < layout.height as isize {
+                true => (
+                    usize::clamp(layout.pos_to_index((0, y)), index, row_end),
+                    usize::clamp(layout.pos_to_index((layout.width as isize, y)), index, row_end),
+                ),
+                false => (row_end, row_end),
+            };
+            let out_index = |index: usize| index - indices.start;
+            for ghost in (index..inside_start).chain(inside_end..row_end) {
+                out_span[out_index(ghost)] = Self::process_job(buffer, ghost, layout);
+            }
+            if inside_start < inside_end {
+                Self::process_inside(buffer, inside_start..inside_end, stride, &mut out_span[out_index(inside_start)..out_index(inside_end)]);
+            }
+            index = row_end;
+        }
+    }
+
+    // Indices are computed from the layout's stride rather than the buffer's, and wrapped ghost cells read the opposite edge
+    fn supports_region_generations() -> bool {
+        false
+    }
+
+    fn supports_inactive_skipping() -> bool {
+        false
+    }
+
+    fn process_job(buffer: &Buffer<GolCell>, index: usize, layout: &PaddedLayout) -> GolCell {
+        let source = match layout.source_index(index) {
+            Some(source) => source,
+            None => return buffer.data[index],
+        };
+        let stride = layout.stride();
+        let neighbor_count = [source - stride - 1, source - stride, source - stride + 1, source - 1, source + 1, source + stride - 1, source + stride, source + stride + 1]
+            .iter()
+            .filter(|neighbor| buffer.data[**neighbor].is_alive())
+            .count();
+        GameOfLifeJobber::next_state(buffer.data[source], neighbor_count)
+    }
+}
@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::sync::OnceLock;
+use serde::{Serialize, Deserialize};
+use crate::parallelism::{Jobber, Buffer};
+use crate::jobbers::gol::{GolCell, for_each_span_row};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructionSet {
+    Avx2,
+    Sse2,
+    Neon,
+    Scalar,
+}
+
+impl InstructionSet {
+    pub fn is_supported(&self) -> bool {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            InstructionSet::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            InstructionSet::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "aarch64")]
+            InstructionSet::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            InstructionSet::Scalar => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    // The widest supported instruction set
+    pub fn detect() -> Self {
+        [InstructionSet::Avx2, InstructionSet::Sse2, InstructionSet::Neon]
+            .iter()
+            .copied()
+            .find(InstructionSet::is_supported)
+            .unwrap_or(InstructionSet::Scalar)
+    }
+}
+
+static INSTRUCTION_SET: OnceLock<InstructionSet> = OnceLock::new();
+
+thread_local! {
+    // Stands in for the rows above and below the buffer, grown to the widest buffer the thread has seen
+    static DEAD_ROW: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+// Game of Life on whole vectors of cells at a time, the instruction set is picked at runtime
+pub struct SimdGolJobber { }
+
+impl SimdGolJobber {
+    pub fn instruction_set() -> InstructionSet {
+        *INSTRUCTION_SET.get_or_init(InstructionSet::detect)
+    }
+
+    // Has to be called before the jobber first runs, returns the instruction set that will be used
+    pub fn force_instruction_set(instruction_set: InstructionSet) -> Result<InstructionSet, String> {
+        if !instruction_set.is_supported() {
+            return Err(format!("Instruction set {:?} is not supported by this CPU", instruction_set));
+        }
+        Ok(*INSTRUCTION_SET.get_or_init(|| instruction_set))
+    }
+
+    fn step_cell(rows: [&[u8]; 3], x: usize) -> u8 {
+        let neighbors = x.saturating_sub(1)..usize::min(x + 2, rows[1].len());
+        let sum: u8 = rows.iter().map(|row| row[neighbors.clone()].iter().sum::<u8>()).sum();
+        let cell = rows[1][x];
+        let neighbor_count = sum - cell;
+        ((neighbor_count == 3) || (neighbor_count == 2 && cell == 1)) as u8
+    }
+
+    // Cells on the row's edges are left to the scalar path so vector loads never read past the row
+    fn step_row(instruction_set: InstructionSet, rows: [&[u8]; 3], cells: Range<usize>, out_row: &mut [u8]) {
+        let width = rows[1].len();
+        let vector_start = usize::max(cells.start, 1);
+        let vector_end = usize::max(usize::min(cells.end, width.saturating_sub(1)), vector_start);
+        let count = vector_end - vector_start;
+        let out_vector = &mut out_row[(vector_start - cells.start)..];
+
+        // SAFETY: the instruction set was checked to be supported, and every load
+        // from x - 1 to x + 1 stays within the rows since 1 <= x < width - 1
+        let vectorized = unsafe {
+            let pointers = [rows[0].as_ptr().add(vector_start), rows[1].as_ptr().add(vector_start), rows[2].as_ptr().add(vector_start)];
+            match instruction_set {
+                #[cfg(target_arch = "x86_64")]
+                InstructionSet::Avx2 => x86::step_avx2(pointers, out_vector.as_mut_ptr(), count),
+                #[cfg(target_arch = "x86_64")]
+                InstructionSet::Sse2 => x86::step_sse2(pointers, out_vector.as_mut_ptr(), count),
+                #[cfg(target_arch = "aarch64")]
+                InstructionSet::Neon => arm::step_neon(pointers, out_vector.as_mut_ptr(), count),
+                _ => 0,
+            }
+        };
+
+        let scalar_cells = (cells.start..vector_start).chain((vector_start + vectorized)..cells.end);
+        for x in scalar_cells {
+            out_row[x - cells.start] = SimdGolJobber::step_cell(rows, x);
+        }
+    }
+}
+
+fn cells_as_bytes(cells: &[GolCell]) -> &[u8] {
+    // SAFETY: GolCell is repr(u8)
+    unsafe { std::slice::from_raw_parts(cells.as_ptr() as *const u8, cells.len()) }
+}
+
+fn cells_as_bytes_mut(cells: &mut [GolCell]) -> &mut [u8] {
+    // SAFETY: GolCell is repr(u8), and only its Dead (0) and Alive (1) values are ever written
+    unsafe { std::slice::from_raw_parts_mut(cells.as_mut_ptr() as *mut u8, cells.len()) }
+}
+
+impl Jobber<GolCell, ()> for SimdGolJobber {
+    fn process_span(buffer: &Buffer<GolCell>, indices: Range<usize>, _conf: &(), out_span: &mut [GolCell]) {
+        let instruction_set = SimdGolJobber::instruction_set();
+        let width = buffer.dims_1d();
+        DEAD_ROW.with(|dead_row| {
+            let mut dead_row = dead_row.borrow_mut();
+            if dead_row.len() < width {
+                dead_row.resize(width, 0);
+            }
+            let dead_row = &dead_row[..width];
+            for_each_span_row(buffer, indices, out_span, |row, out_row| {
+                let rows = [
+                    row.above.map_or(dead_row, cells_as_bytes),
+                    cells_as_bytes(row.current),
+                    row.below.map_or(dead_row, cells_as_bytes),
+                ];
+                SimdGolJobber::step_row(instruction_set, rows, row.cells, cells_as_bytes_mut(out_row));
+            });
+        });
+    }
+
+    fn process_job(buffer: &Buffer<GolCell>, index: usize, conf: &()) -> GolCell {
+        let mut out = [GolCell::Dead];
+        SimdGolJobber::process_span(buffer, index..(index + 1), conf, &mut out);
+        out[0]
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    // Each step takes pointers to the first cell of the rows above, at and below the cells to compute,
+    // and returns how many cells it computed, always a multiple of the vector width
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn step_avx2(rows: [*const u8; 3], out: *mut u8, count: usize) -> usize {
+        let one = _mm256_set1_epi8(1);
+        let two = _mm256_set1_epi8(2);
+        let three = _mm256_set1_epi8(3);
+        let mut x = 0;
+        while x + 32 <= count {
+            let mut sum = _mm256_setzero_si256();
+            for row in rows {
+                sum = _mm256_add_epi8(sum, _mm256_loadu_si256(row.add(x).sub(1) as *const __m256i));
+                sum = _mm256_add_epi8(sum, _mm256_loadu_si256(row.add(x) as *const __m256i));
+                sum = _mm256_add_epi8(sum, _mm256_loadu_si256(row.add(x + 1) as *const __m256i));
+            }
+            let cell = _mm256_loadu_si256(rows[1].add(x) as *const __m256i);
+            let neighbor_count = _mm256_sub_epi8(sum, cell);
+            let alive = _mm256_or_si256(
+                _mm256_cmpeq_epi8(neighbor_count, three),
+                _mm256_and_si256(_mm256_cmpeq_epi8(neighbor_count, two), _mm256_cmpeq_epi8(cell, one)),
+            );
+            _mm256_storeu_si256(out.add(x) as *mut __m256i, _mm256_and_si256(alive, one));
+            x += 32;
+        }
+        x
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn step_sse2(rows: [*const u8; 3], out: *mut u8, count: usize) -> usize {
+        let one = _mm_set1_epi8(1);
+        let two = _mm_set1_epi8(2);
+        let three = _mm_set1_epi8(3);
+        let mut x = 0;
+        while x + 16 <= count {
+            let mut sum = _mm_setzero_si128();
+            for row in rows {
+                sum = _mm_add_epi8(sum, _mm_loadu_si128(row.add(x).sub(1) as *const __m128i));
+                sum = _mm_add_epi8(sum, _mm_loadu_si128(row.add(x) as *const __m128i));
+                sum = _mm_add_epi8(sum, _mm_loadu_si128(row.add(x + 1) as *const __m128i));
+            }
+            let cell = _mm_loadu_si128(rows[1].add(x) as *const __m128i);
+            let neighbor_count = _mm_sub_epi8(sum, cell);
+            let alive = _mm_or_si128(
+                _mm_cmpeq_epi8(neighbor_count, three),
+                _mm_and_si128(_mm_cmpeq_epi8(neighbor_count, two), _mm_cmpeq_epi8(cell, one)),
+            );
+            _mm_storeu_si128(out.add(x) as *mut __m128i, _mm_and_si128(alive, one));
+            x += 16;
+        }
+        x
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn step_neon(rows: [*const u8; 3], out: *mut u8, count: usize) -> usize {
+        let one = vdupq_n_u8(1);
+        let two = vdupq_n_u8(2);
+        let three = vdupq_n_u8(3);
+        let mut x = 0;
+        while x + 16 <= count {
+            let mut sum = vdupq_n_u8(0);
+            for row in rows {
+                sum = vaddq_u8(sum, vld1q_u8(row.add(x).sub(1)));
+                sum = vaddq_u8(sum, vld1q_u8(row.add(x)));
+                sum = vaddq_u8(sum, vld1q_u8(row.add(x + 1)));
+            }
+            let cell = vld1q_u8(rows[1].add(x));
+            let neighbor_count = vsubq_u8(sum, cell);
+            let alive = vorrq_u8(
+                vceqq_u8(neighbor_count, three),
+                vandq_u8(vceqq_u8(neighbor_count, two), vceqq_u8(cell, one)),
+            );
+            vst1q_u8(out.add(x), vandq_u8(alive, one));
+            x += 16;
+        }
+        x
+    }
+}
@@ -1,2 +1,6 @@
 pub mod checkerboard;
 pub mod gol;
+pub mod gol_lut;
+pub mod gol_simd;
+pub mod gol_padded;
+pub mod gol_morton;
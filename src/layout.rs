@@ -0,0 +1,56 @@
+use crate::parallelism::{Buffer, Executor, ExecutorError, RunLength, check_buffers};
+use crate::trace::Trace;
+
+// A buffer layout other than row-major, for width by height grids. The layout may add cells of its own
+// around or between the grid's, which get the fill value when converting to it and are dropped when converting back.
+pub trait LayoutConversion: Copy {
+    fn to_layout<T: Copy>(self, buffer: &Buffer<T>, fill: T) -> Buffer<T>;
+    fn to_row_major<T: Copy>(self, converted: &Buffer<T>, out: &mut [T]);
+}
+
+// Runs an executor of buffers in another layout behind the row-major interface, converting the buffers on the way in and out.
+// The timings include the conversions, which only happen once per run.
+pub struct ExecutorLayout<T, TLayout> {
+    inner: Box<dyn Executor<T, TLayout>>,
+    // Builds the layout of each run from the dimensions of its buffer
+    layout_for: Box<dyn Fn((usize, usize)) -> TLayout>,
+    fill: T,
+}
+
+impl<T, TLayout> ExecutorLayout<T, TLayout> {
+    pub fn new(inner: Box<dyn Executor<T, TLayout>>, layout_for: impl Fn((usize, usize)) -> TLayout + 'static, fill: T) -> Self {
+        Self { inner, layout_for: Box::new(layout_for), fill }
+    }
+}
+
+impl<T, TLayout> Executor<T, ()> for ExecutorLayout<T, TLayout>
+    where
+        T: Copy,
+        TLayout: LayoutConversion,
+{
+    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], _conf: ()) -> Result<Buffer<T>, ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
+        let layout = (self.layout_for)(in_buffer.dims_2d());
+        let converted = layout.to_layout(&in_buffer, self.fill);
+        let mut converted_out = Buffer::clone(&converted);
+        self.inner.compute(converted, &mut converted_out.data, layout)?;
+        layout.to_row_major(&converted_out, out_buffer);
+        Ok(in_buffer)
+    }
+
+    fn print_stats(&self) {
+        self.inner.print_stats();
+    }
+
+    fn trace(&self) -> Trace {
+        self.inner.trace()
+    }
+
+    fn advance(&self, length: RunLength, mut buffer: Buffer<T>, _conf: ()) -> Result<(Buffer<T>, usize), ExecutorError> {
+        check_buffers(&buffer, &buffer.data)?;
+        let layout = (self.layout_for)(buffer.dims_2d());
+        let (converted, completed) = self.inner.advance(length, layout.to_layout(&buffer, self.fill), layout)?;
+        layout.to_row_major(&converted, &mut buffer.data);
+        Ok((buffer, completed))
+    }
+}
@@ -1,33 +1,153 @@
 mod parallelism;
 use crate::parallelism::*;
 
+mod topology;
+use crate::topology::*;
+
+mod hashlife;
+use crate::hashlife::*;
+
+mod universe;
+use crate::universe::*;
+
+mod pattern;
+use crate::pattern::*;
+
+mod layout;
+use crate::layout::*;
+
+mod padded;
+use crate::padded::*;
+
+mod morton;
+use crate::morton::*;
+
+mod wavefront;
+use crate::wavefront::*;
+
+mod queues;
+use crate::queues::*;
+
+mod interrupt;
+use crate::interrupt::*;
+
+mod burn;
+use crate::burn::*;
+
+mod stress;
+use crate::stress::*;
+
+mod trace;
+use crate::trace::*;
+
+mod perf;
+use crate::perf::*;
+
 mod jobbers;
 use crate::jobbers::checkerboard::*;
 use crate::jobbers::gol::*;
+use crate::jobbers::gol_lut::*;
+use crate::jobbers::gol_simd::*;
+use crate::jobbers::gol_padded::*;
+use crate::jobbers::gol_morton::*;
 
 use serde::{Serialize, Deserialize};
 use terminal_size::{Width, Height, terminal_size};
+use std::time::{Duration, Instant};
 
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum DecompositionMode {
+    Slices,
+    Tiles,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum GolJobberKind {
+    Reference,
+    LookupTable,
+    Simd,
+}
+
 #[derive(Deserialize)]
 struct ConfigToml {
     parallel_execution: Option<bool>,
     thread_count: Option<usize>,
+    thread_selection: Option<ThreadSelection>,
+    pin_threads: Option<bool>,
+    main_thread_works: Option<bool>,
+    queue_backend: Option<QueueBackend>,
     work_slice_len: Option<usize>,
+    decomposition: Option<DecompositionMode>,
+    tile_width: Option<usize>,
+    tile_height: Option<usize>,
+    generations_per_sync: Option<usize>,
+    skip_inactive: Option<bool>,
+    phase_timing: Option<bool>,
+    worker_stats: Option<bool>,
+    trace_file: Option<String>,
+    perf_counters: Option<bool>,
+    scheduling: Option<Scheduling>,
+    chunk_size: Option<usize>,
+    jobber: Option<GolJobberKind>,
+    instruction_set: Option<InstructionSet>,
+    padding: Option<usize>,
+    boundary: Option<BoundaryMode>,
+    layout: Option<BufferLayout>,
 
     iterations: Option<usize>,
+    duration: Option<String>,
+    throttle_threshold: Option<f32>,
+    stress_reference: Option<GolJobberKind>,
+    stress_check_interval: Option<usize>,
     width: Option<usize>,
     height: Option<usize>,
 }
 
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct Config {
     parallel_execution: bool,
     thread_count: usize,
+    thread_selection: ThreadSelection,
+    pin_threads: bool,
+    // Whether the dispatching thread runs jobs too, counting as one of the thread_count threads
+    main_thread_works: bool,
+    queue_backend: QueueBackend,
     work_slice_len: usize,
+    decomposition: DecompositionMode,
+    tile_width: usize,
+    tile_height: usize,
+    generations_per_sync: usize,
+    skip_inactive: bool,
+    // Reports the time parallel executors spend enqueuing jobs, waiting, copying results back and unwrapping the buffer
+    phase_timing: bool,
+    worker_stats: bool,
+    // Chrome trace-event file the jobs and generations of barrier scheduled parallel runs are written to
+    trace_file: Option<String>,
+    // Counts instructions, cycles, cache and branch misses of the benchmark runs with perf_event_open, on Linux
+    perf_counters: bool,
+    scheduling: Scheduling,
+    chunk_size: usize,
+    jobber: GolJobberKind,
+    // Used by the simd jobber, the widest supported one is detected when omitted
+    instruction_set: Option<InstructionSet>,
+    // Width of the ghost border around the buffer, 0 runs the unpadded jobbers
+    padding: usize,
+    boundary: BoundaryMode,
+    layout: BufferLayout,
 
     iterations: usize,
+    // Wall-clock budget of the benchmark runs, e.g. "30s", replacing the iteration count when set
+    duration: Option<String>,
+    // Percentage below the first minute's peak throughput a burn counts as throttled at
+    throttle_threshold: f32,
+    // Jobber the stress test recomputes generations with on a single thread, the same as jobber or another one
+    stress_reference: GolJobberKind,
+    // The stress test verifies a block of generations out of every stress_check_interval
+    stress_check_interval: usize,
     width: usize,
     height: usize,
 }
@@ -37,9 +157,33 @@ impl Default for Config {
         Self {
             parallel_execution: true,
             thread_count: num_cpus::get(),
+            thread_selection: ThreadSelection::Logical,
+            pin_threads: false,
+            main_thread_works: false,
+            queue_backend: QueueBackend::SegQueue,
             work_slice_len: 128 * 128,
+            decomposition: DecompositionMode::Slices,
+            tile_width: 128,
+            tile_height: 128,
+            generations_per_sync: 1,
+            skip_inactive: false,
+            phase_timing: false,
+            worker_stats: false,
+            trace_file: None,
+            perf_counters: false,
+            scheduling: Scheduling::Barrier,
+            chunk_size: 64,
+            jobber: GolJobberKind::Reference,
+            instruction_set: None,
+            padding: 0,
+            boundary: BoundaryMode::Fill,
+            layout: BufferLayout::RowMajor,
 
             iterations: 1024,
+            duration: None,
+            throttle_threshold: 10_f32,
+            stress_reference: GolJobberKind::Reference,
+            stress_check_interval: 16,
             width: 3840,
             height: 2160,
         }
@@ -49,27 +193,346 @@ impl Default for Config {
 impl From<ConfigToml> for Config {
     fn from(toml: ConfigToml) -> Self {
         let default = Config::default();
+        let thread_selection = toml.thread_selection.unwrap_or(default.thread_selection);
         Self {
             parallel_execution: toml.parallel_execution.unwrap_or(default.parallel_execution),
-            thread_count: toml.thread_count.unwrap_or(default.thread_count),
+            // Without an explicit count, spawn one thread per CPU of the selection
+            thread_count: toml.thread_count.unwrap_or_else(|| selection_thread_count(CpuTopology::detect().as_ref(), thread_selection)),
+            thread_selection,
+            pin_threads: toml.pin_threads.unwrap_or(default.pin_threads),
+            main_thread_works: toml.main_thread_works.unwrap_or(default.main_thread_works),
+            queue_backend: toml.queue_backend.unwrap_or(default.queue_backend),
             work_slice_len: toml.work_slice_len.unwrap_or(default.work_slice_len),
+            decomposition: toml.decomposition.unwrap_or(default.decomposition),
+            tile_width: toml.tile_width.unwrap_or(default.tile_width),
+            tile_height: toml.tile_height.unwrap_or(default.tile_height),
+            generations_per_sync: toml.generations_per_sync.unwrap_or(default.generations_per_sync),
+            skip_inactive: toml.skip_inactive.unwrap_or(default.skip_inactive),
+            phase_timing: toml.phase_timing.unwrap_or(default.phase_timing),
+            worker_stats: toml.worker_stats.unwrap_or(default.worker_stats),
+            trace_file: toml.trace_file.or(default.trace_file),
+            perf_counters: toml.perf_counters.unwrap_or(default.perf_counters),
+            scheduling: toml.scheduling.unwrap_or(default.scheduling),
+            chunk_size: toml.chunk_size.unwrap_or(default.chunk_size),
+            jobber: toml.jobber.unwrap_or(default.jobber),
+            instruction_set: toml.instruction_set.or(default.instruction_set),
+            padding: toml.padding.unwrap_or(default.padding),
+            boundary: toml.boundary.unwrap_or(default.boundary),
+            layout: toml.layout.unwrap_or(default.layout),
 
             iterations: toml.iterations.unwrap_or(default.iterations),
+            duration: toml.duration.or(default.duration),
+            throttle_threshold: toml.throttle_threshold.unwrap_or(default.throttle_threshold),
+            stress_reference: toml.stress_reference.unwrap_or(default.stress_reference),
+            stress_check_interval: toml.stress_check_interval.unwrap_or(default.stress_check_interval),
             width: toml.width.unwrap_or(default.width),
             height: toml.height.unwrap_or(default.height),
         }
     }
 }
 
+impl Config {
+    fn run_length(&self) -> Result<RunLength, String> {
+        match &self.duration {
+            Some(duration) => Ok(RunLength::Duration(parse_duration(duration)?)),
+            None => Ok(RunLength::Iterations(self.iterations)),
+        }
+    }
+
+    fn work_decomposition(&self, mode: DecompositionMode) -> WorkDecomposition {
+        match mode {
+            DecompositionMode::Slices => WorkDecomposition::Slices(self.work_slice_len),
+            DecompositionMode::Tiles => WorkDecomposition::Tiles(self.tile_width, self.tile_height),
+        }
+    }
+}
+
+fn build_gol_executor<TJobber>(config: &Config, decomposition: WorkDecomposition, pinned_cpus: &[usize]) -> Result<Box<dyn Executor<GolCell, ()>>, ExecutorError>
+    where TJobber: 'static + Jobber<GolCell, ()>
+{
+    Ok(match (config.parallel_execution, config.scheduling) {
+        (true, Scheduling::Barrier) => Box::new(
+            ExecutorParallel::new_pinned::<TJobber>(
+                config.thread_count,
+                decomposition,
+                pinned_cpus,
+                config.main_thread_works,
+                config.queue_backend,
+            )
+                .with_generations_per_sync(config.generations_per_sync)?
+                .with_inactive_skipping(config.skip_inactive)?
+                .with_phase_timing(config.phase_timing)
+                .with_worker_stats(config.worker_stats)
+                .with_tracing(config.trace_file.is_some())
+        ),
+        (true, Scheduling::Wavefront) => Box::new(
+            ExecutorWavefront::<GolCell, (), TJobber>::new(config.thread_count, decomposition, pinned_cpus, config.main_thread_works)?
+        ),
+        (false, _) => Box::new(ExecutorSingleThread::<GolCell, (), TJobber>::new()),
+    })
+}
+
+fn build_gol_executor_kind(kind: GolJobberKind, config: &Config, decomposition: WorkDecomposition, pinned_cpus: &[usize]) -> Result<Box<dyn Executor<GolCell, ()>>, ExecutorError> {
+    match kind {
+        GolJobberKind::Reference => build_gol_executor::<GameOfLifeJobber>(config, decomposition, pinned_cpus),
+        GolJobberKind::LookupTable => build_gol_executor::<LookupTableGolJobber>(config, decomposition, pinned_cpus),
+        GolJobberKind::Simd => build_gol_executor::<SimdGolJobber>(config, decomposition, pinned_cpus),
+    }
+}
+
+// Options a layout's jobber doesn't support are left out, which is worth a warning since the config asked for them
+fn warn_unsupported<TJobber, TConf>(config: &Config, buffers: &str)
+    where TJobber: Jobber<GolCell, TConf>
+{
+    if config.parallel_execution && config.scheduling == Scheduling::Wavefront && !TJobber::supports_region_generations() {
+        println!("{} buffers don't support wavefront scheduling, barrier scheduling will be used", buffers);
+    }
+    if config.generations_per_sync > 1 && !TJobber::supports_region_generations() {
+        println!("{} buffers don't support temporal blocking, generations_per_sync will be ignored", buffers);
+    }
+    if config.skip_inactive && !TJobber::supports_inactive_skipping() {
+        println!("{} buffers don't support inactive skipping, skip_inactive will be ignored", buffers);
+    }
+}
+
+// Padded buffers have their own jobber, the decomposition covering the ghost border too
+fn build_padded_gol_executor(config: &Config, padding: usize, pinned_cpus: &[usize]) -> Box<dyn Executor<GolCell, ()>> {
+    warn_unsupported::<PaddedGolJobber, PaddedLayout>(config, "Padded");
+    let inner: Box<dyn Executor<GolCell, PaddedLayout>> = match config.parallel_execution {
+        true => Box::new(ExecutorParallel::new_pinned::<PaddedGolJobber>(
+            config.thread_count,
+            config.work_decomposition(config.decomposition),
+            pinned_cpus,
+            config.main_thread_works,
+            config.queue_backend,
+        )
+            .with_phase_timing(config.phase_timing)
+            .with_worker_stats(config.worker_stats)
+            .with_tracing(config.trace_file.is_some())),
+        false => Box::new(ExecutorSingleThread::<GolCell, PaddedLayout, PaddedGolJobber>::new()),
+    };
+    let boundary = config.boundary;
+    Box::new(ExecutorLayout::new(inner, move |dims| PaddedLayout::new(dims, padding, boundary), GolCell::Dead))
+}
+
+// Morton buffers are tiled in aligned squares
+fn build_morton_gol_executor(config: &Config, pinned_cpus: &[usize]) -> Box<dyn Executor<GolCell, ()>> {
+    warn_unsupported::<MortonGolJobber, MortonLayout>(config, "Morton");
+    let inner: Box<dyn Executor<GolCell, MortonLayout>> = match config.parallel_execution {
+        true => Box::new(ExecutorParallel::new_pinned::<MortonGolJobber>(
+            config.thread_count,
+            WorkDecomposition::Slices(MortonLayout::tile_len(config.tile_width)),
+            pinned_cpus,
+            config.main_thread_works,
+            config.queue_backend,
+        )
+            .with_phase_timing(config.phase_timing)
+            .with_worker_stats(config.worker_stats)
+            .with_tracing(config.trace_file.is_some())),
+        false => Box::new(ExecutorSingleThread::<GolCell, MortonLayout, MortonGolJobber>::new()),
+    };
+    Box::new(ExecutorLayout::new(inner, MortonLayout::new, GolCell::Dead))
+}
+
+// What the stress test compares the benchmark executor's generations with
+fn build_stress_reference(config: &Config) -> Result<Box<dyn Executor<GolCell, ()>>, ExecutorError> {
+    let single_thread = Config { parallel_execution: false, ..config.clone() };
+    build_gol_executor_kind(config.stress_reference, &single_thread, single_thread.work_decomposition(single_thread.decomposition), &[])
+}
+
+fn build_benchmark_executor(config: &Config, pinned_cpus: &[usize]) -> Result<Box<dyn Executor<GolCell, ()>>, ExecutorError> {
+    match (config.layout, config.padding) {
+        (BufferLayout::Morton, _) => Ok(build_morton_gol_executor(config, pinned_cpus)),
+        (BufferLayout::RowMajor, 0) => build_gol_executor_kind(config.jobber, config, config.work_decomposition(config.decomposition), pinned_cpus),
+        (BufferLayout::RowMajor, padding) => Ok(build_padded_gol_executor(config, padding, pinned_cpus)),
+    }
+}
+
+// Each comparison splits every benchmark run in one run per compared variant, labelled with what it compares
+fn compare_runs<F>(runs: Vec<(Vec<String>, Config)>, variants: F) -> Vec<(Vec<String>, Config)>
+    where F: Fn(&Config) -> Vec<(String, Config)>
+{
+    runs
+        .into_iter()
+        .flat_map(|(labels, config)| variants(&config)
+            .into_iter()
+            .map(move |(label, variant)| {
+                let mut labels = labels.clone();
+                labels.push(label);
+                (labels, variant)
+            })
+        )
+        .collect()
+}
+
+// Selections other than logical only make sense if the threads stay on the selected CPUs
+fn select_pinned_cpus(config: &Config) -> Vec<usize> {
+    match (config.pin_threads || config.thread_selection != ThreadSelection::Logical, CpuTopology::detect()) {
+        (true, Some(topology)) => topology.select(config.thread_selection),
+        (true, None) => {
+            println!("Unable to read the CPU topology, threads will not be pinned");
+            Vec::new()
+        },
+        (false, _) => Vec::new(),
+    }
+}
+
+const AUTOTUNE_ITERATIONS: usize = 32;
+
+// A slice's input and output take twice its length (cells are single bytes), so slices filling half or a quarter
+// of each data cache are tried, capped at an even split of the buffer between the threads
+fn slice_len_candidates(caches: &[CacheInfo], config: &Config) -> Vec<usize> {
+    let even_split = usize::max(config.width * config.height / usize::max(config.thread_count, 1), 1);
+    let mut candidates: Vec<usize> = caches
+        .iter()
+        .filter(|cache| cache.holds_data())
+        .flat_map(|cache| [cache.size / 4, cache.size / 2])
+        .chain(std::iter::once(Config::default().work_slice_len))
+        .map(|work_slice_len| work_slice_len.clamp(1, even_split))
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+// Thread counts of each distinct thread selection, or the reported CPU counts when the topology can't be read
+fn thread_candidates(config: &Config) -> Vec<(usize, ThreadSelection)> {
+    let mut candidates: Vec<(usize, ThreadSelection)> = match CpuTopology::detect() {
+        Some(topology) => {
+            let mut selected: Vec<Vec<usize>> = Vec::new();
+            [ThreadSelection::Logical, ThreadSelection::Physical, ThreadSelection::Performance]
+                .iter()
+                .filter_map(|&selection| {
+                    let cpus = topology.select(selection);
+                    let thread_count = cpus.len();
+                    if selected.contains(&cpus) {
+                        return None;
+                    }
+                    selected.push(cpus);
+                    Some((thread_count, selection))
+                })
+                .collect()
+        },
+        None => vec![(num_cpus::get(), ThreadSelection::Logical), (num_cpus::get_physical(), ThreadSelection::Logical)],
+    };
+    candidates.dedup();
+    // An explicit thread count from the config is tried too
+    if candidates.iter().all(|(thread_count, _)| *thread_count != config.thread_count) {
+        candidates.push((config.thread_count, config.thread_selection));
+    }
+    candidates
+}
+
+// Quick search of the slice length with the configured threads, then of the thread selection with the best slice length,
+// timing a short run of each candidate. Slice lengths only matter for parallel runs of flat slices on row-major buffers.
+fn autotune(config: &Config, init_buf: &Buffer<GolCell>) -> Result<Config, ExecutorError> {
+    let iterations = usize::min(config.iterations, AUTOTUNE_ITERATIONS);
+    let fastest = |candidates: Vec<(String, Config)>| -> Result<Config, ExecutorError> {
+        let mut best: Option<(f32, Config)> = None;
+        for (label, candidate) in candidates {
+            println!("Trying {}", label);
+            let exec_gol = build_benchmark_executor(&candidate, &select_pinned_cpus(&candidate))?;
+            let now = Instant::now();
+            exec_gol.compute_iterations(iterations, Buffer::clone(init_buf), ())?;
+            let elapsed = now.elapsed().as_secs_f32();
+            if best.as_ref().is_none_or(|(best_elapsed, _)| elapsed < *best_elapsed) {
+                best = Some((elapsed, candidate));
+            }
+        }
+        Ok(best.unwrap().1)
+    };
+
+    let base = Config {
+        parallel_execution: true,
+        decomposition: DecompositionMode::Slices,
+        padding: 0,
+        layout: BufferLayout::RowMajor,
+        ..config.clone()
+    };
+    let caches = detect_caches();
+    for cache in &caches {
+        println!("L{} {} cache: {} KiB", cache.level, cache.kind, cache.size / 1024);
+    }
+    println!("Autotuning with {} iterations of a {}x{} buffer", iterations, config.width, config.height);
+
+    let best = fastest(slice_len_candidates(&caches, &base)
+        .into_iter()
+        .map(|work_slice_len| (
+            format!("work_slice_len {} with {} thread(s)", work_slice_len, base.thread_count),
+            Config { work_slice_len, ..base.clone() },
+        ))
+        .collect()
+    )?;
+    let best = fastest(thread_candidates(&best)
+        .into_iter()
+        .map(|(thread_count, thread_selection)| (
+            format!("work_slice_len {} with {} thread(s), {:?} selection", best.work_slice_len, thread_count, thread_selection),
+            Config { thread_count, thread_selection, ..best.clone() },
+        ))
+        .collect()
+    )?;
+    println!(
+        "Fastest: work_slice_len {} with {} thread(s), {:?} selection",
+        best.work_slice_len,
+        best.thread_count,
+        best.thread_selection,
+    );
+    Ok(best)
+}
+
+// Durations like "500ms", "30s", "2m" or "1h", a bare number counting seconds
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let unit_start = value.find(|character: char| character.is_ascii_alphabetic()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let number = number.trim().parse::<f64>().map_err(|_| format!("Unable to parse duration '{}'", value))?;
+    let seconds = match unit {
+        "ms" => number / 1000_f64,
+        "" | "s" => number,
+        "m" => number * 60_f64,
+        "h" => number * 3600_f64,
+        _ => return Err(format!("Unknown unit in duration '{}', use ms, s, m or h", value)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("Invalid duration '{}'", value))
+}
+
 fn format_file_name_to_toml(file_name: &str) -> String {
     format!("{}.toml", file_name)
 }
 
+fn format_file_name_to_cells(file_name: &str) -> String {
+    format!("{}.cells", file_name)
+}
+
+fn format_file_name_to_json(file_name: &str) -> String {
+    format!("{}.json", file_name)
+}
+
+fn save_traces(file_name: &str, traces: &[(String, Trace)]) -> Result<(), AppError> {
+    if traces.iter().all(|(_, trace)| trace.is_empty()) {
+        println!("Nothing was traced, only barrier scheduled parallel runs are");
+        return Ok(());
+    }
+    write_chrome_trace(file_name, traces).map_err(|error| format!("Unable to write trace file {}: {}", file_name, error))?;
+    let job_count: usize = traces.iter().map(|(_, trace)| trace.jobs.len()).sum();
+    println!("Wrote a trace of {} jobs to '{}'", job_count, file_name);
+    Ok(())
+}
+
+// Saves the buffer of an interrupted run if asked to, then ends the program
+fn stop_interrupted(buffer: Buffer<GolCell>, save_file_name: Option<&str>) -> Result<(), AppError> {
+    if let Some(file_name) = save_file_name {
+        Pattern { cells: buffer }.save(file_name)?;
+        println!("Saved the interrupted run's buffer to '{}'", file_name);
+    }
+    Err(AppError::Interrupted)
+}
+
 const HELP_STRING: &'static str = "
 A tool for benchmarking CPUs using Conway's Game of Life.
 It will run for the specified iteration count, simulating Game of Life generations.
 
 You can configure the run parameters using the --generate-config and --use-config flags.
+Flags can be combined, e.g. --use-config my_conf --compare-decomposition.
 
 flags:
     (-g | --generate-config) <filename?>
@@ -80,52 +543,229 @@ flags:
         to use a config file instead of default parameters
         any parameter be omitted if the default is preferred
         the filename is optional, the tool will search for bench_conf.toml by default
+    (-a | --autotune) <filename?>
+        to search for the work_slice_len and thread count that run fastest on this machine
+        trying slice lengths sized after the detected CPU caches, then each thread selection,
+        and write the fastest configuration to a config file (bench_conf.toml by default)
     (-v | --visualize | --visualise) <target_framerate?>
         to run the tool in visualization mode
         in which it'll display a Game of Life simulation sized to the terminal window
+    (-t | --topology)
+        to print the detected CPU topology (cores, SMT siblings, packages, capacities)
+        and the thread count each thread_selection config value would use
+    (-d | --compare-decomposition)
+        to run the benchmark twice, once with flat work slices and once with 2D tiles
+        so the cache behavior of both decompositions can be compared
+    (-x | --cross-check)
+        to run the benchmark with every Game of Life jobber (reference, lookup table and simd)
+        and verify that they all produce the same buffer as the reference
+    (-p | --compare-padding)
+        to run the benchmark twice, once on the unpadded buffer and once on a buffer
+        surrounded by a ghost border of padding cells (at least one), which needs no bounds checks
+        both with the reference jobber on the same threads and decomposition
+    (-m | --compare-layout)
+        to run the benchmark twice, once on a row-major buffer and once on a Morton (Z-order) one
+        tiled in aligned squares of tile_width rounded up to a power of two
+    (-q | --compare-queues)
+        to run the benchmark once per job queue backend (seg_queue, array_queue, mutex_condvar and channel)
+        so the synchronization primitives handing jobs to the threads can be compared
+    (-l | --hashlife) <pattern_file?>
+        to advance the configured iteration count of generations with the HashLife algorithm
+        on an unbounded universe, which can run millions of generations of patterns that repeat
+        the pattern is read from an RLE (.rle) or plaintext (.cells) file
+        without one, the usual checkerboard buffer is used
+    (-u | --unbounded) <pattern_file?>
+        to run the configured iteration count of generations on an unbounded universe
+        made of chunk_size by chunk_size chunks, allocated as live cells approach them
+        the pattern is read the same way as with --hashlife
+    (-b | --burn) <duration?>
+        to keep the benchmark running for the duration (5m by default, e.g. 90s or 1h)
+        printing the throughput of every second, and report whether it dropped more than
+        throttle_threshold percent below the first minute's peak, e.g. due to thermal throttling
+    (-r | --stress) <duration?>
+        to run the benchmark for the duration (10m by default) as a stability test
+        recomputing a block of generations out of every stress_check_interval on a single thread with the
+        stress_reference jobber, any difference being reported as a hardware error
+    (-e | --trace) <filename?>
+        to record when each job ran on which thread, and when each generation started and ended
+        writing them in the Chrome trace-event format (trace.json by default), which Perfetto
+        and chrome://tracing can open, only barrier scheduled parallel runs are traced
+    (-s | --save-interrupted) <filename?>
+        to save the buffer of a run interrupted with Ctrl-C as a plaintext pattern
+        which --hashlife and --unbounded can load, interrupted.cells by default
 ";
 
-fn run() -> Result<(), String> {
+// Everything ending the program early, each kind of error exiting with its own code
+enum AppError {
+    // Invalid arguments, unreadable files, failed cross-checks and the like
+    Failure(String),
+    Executor(ExecutorError),
+    // The stress test caught the executor computing a wrong generation
+    HardwareError(String),
+    // Ctrl-C was pressed, the run already reported what it completed
+    Interrupted,
+}
+
+impl AppError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Failure(_) => 1,
+            AppError::Executor(ExecutorError::WorkerFailure(_)) => 2,
+            AppError::Executor(ExecutorError::Io(_)) => 3,
+            AppError::Executor(ExecutorError::InvalidDimensions(_)) => 4,
+            AppError::HardwareError(_) => 5,
+            AppError::Executor(ExecutorError::Unsupported(_)) => 6,
+            // Like a process killed by SIGINT
+            AppError::Interrupted => 130,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Failure(message) => write!(f, "{}", message),
+            AppError::Executor(error) => write!(f, "{}", error),
+            AppError::HardwareError(message) => write!(f, "Hardware error: {}", message),
+            AppError::Interrupted => write!(f, "Interrupted"),
+        }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Failure(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Failure(String::from(message))
+    }
+}
+
+impl From<ExecutorError> for AppError {
+    fn from(error: ExecutorError) -> Self {
+        AppError::Executor(error)
+    }
+}
+
+fn run() -> Result<(), AppError> {
     const DEFAULT_CONF_FILE_NAME: &'static str = "bench_conf";
+    const DEFAULT_INTERRUPTED_FILE_NAME: &str = "interrupted";
+    const DEFAULT_BURN_DURATION: &str = "5m";
+    const DEFAULT_STRESS_DURATION: &str = "10m";
+    const DEFAULT_TRACE_FILE_NAME: &str = "trace";
     let mut config = Config::default();
     let mut vis_mode = false;
     let mut target_framerate: usize = 8;
+    let mut compare_decomposition = false;
+    let mut compare_padding = false;
+    let mut compare_layout = false;
+    let mut compare_queues = false;
+    let mut autotune_file_name: Option<String> = None;
+    let mut cross_check = false;
+    let mut hashlife_mode = false;
+    let mut unbounded_mode = false;
+    let mut pattern_file: Option<String> = None;
+    let mut save_file_name: Option<String> = None;
+    let mut burn_duration: Option<Duration> = None;
+    let mut stress_duration: Option<Duration> = None;
+    let mut trace_file_name: Option<String> = None;
 
-    let mut args_iter = std::env::args().skip(1);
-    if let Some(arg) = args_iter.next() {
+    let mut args_iter = std::env::args().skip(1).peekable();
+    while let Some(arg) = args_iter.next() {
+        // Optional flag values can't start with a dash, so they aren't mistaken for the next flag
+        let mut next_value = || args_iter.next_if(|value| !value.starts_with('-'));
         match arg.as_str() {
             "-h" | "--help" => {
                 println!("{}", HELP_STRING.trim());
                 return Ok(());
             },
             "-g" | "--generate-config" => {
-                let file_name = format_file_name_to_toml(&args_iter.next().unwrap_or(String::from(DEFAULT_CONF_FILE_NAME)));
+                let file_name = format_file_name_to_toml(&next_value().unwrap_or_else(|| String::from(DEFAULT_CONF_FILE_NAME)));
                 let conf_serialized = toml::to_string(&config).unwrap();
                 std::fs::write(&file_name, conf_serialized).map_err(|_| "Unable to write to file, exiting.")?;
                 println!("Generated config file '{}', exiting.", file_name);
                 return Ok(());
             },
             "-c" | "--use-config" => {
-                let file_name = format_file_name_to_toml(&args_iter.next().unwrap_or(String::from(DEFAULT_CONF_FILE_NAME)));
+                let file_name = format_file_name_to_toml(&next_value().unwrap_or_else(|| String::from(DEFAULT_CONF_FILE_NAME)));
                 let conf_seriazlied = std::fs::read_to_string(&file_name).map_err(|_| format!("Unable to find or read file {}, exiting.", file_name))?;
                 let conf_deserialized: ConfigToml = toml::from_str(&conf_seriazlied).map_err(|_| "Unable to parse file's values, generate one to see available fields.")?;
                 config = conf_deserialized.into();
                 println!("Using config file '{}'", file_name);
             },
+            "-a" | "--autotune" => {
+                autotune_file_name = Some(format_file_name_to_toml(&next_value().unwrap_or_else(|| String::from(DEFAULT_CONF_FILE_NAME))));
+            },
+            "-t" | "--topology" => {
+                let topology = CpuTopology::detect();
+                match &topology {
+                    Some(topology) => print!("{}", topology),
+                    None => println!("Unable to read the CPU topology, falling back to reported CPU counts"),
+                }
+                for cache in detect_caches() {
+                    println!("L{} {} cache: {} KiB, shared by cpus {:?}", cache.level, cache.kind, cache.size / 1024, cache.shared_cpus);
+                }
+                for selection in [ThreadSelection::Logical, ThreadSelection::Physical, ThreadSelection::Performance] {
+                    println!("thread_selection {:?}: {} thread(s)", selection, selection_thread_count(topology.as_ref(), selection));
+                }
+                return Ok(());
+            },
             "-v" | "--visualize" | "--visualise" => {
                 vis_mode = true;
-                if let Some(framerate_string) = args_iter.next() {
+                if let Some(framerate_string) = next_value() {
                     let framerate = framerate_string.parse::<usize>().map_err(|_| "Unable to parse target framerate")?;
                     target_framerate = framerate;
                 }
             },
+            "-d" | "--compare-decomposition" => {
+                compare_decomposition = true;
+            },
+            "-p" | "--compare-padding" => {
+                compare_padding = true;
+            },
+            "-m" | "--compare-layout" => {
+                compare_layout = true;
+            },
+            "-q" | "--compare-queues" => {
+                compare_queues = true;
+            },
+            "-x" | "--cross-check" => {
+                cross_check = true;
+            },
+            "-l" | "--hashlife" => {
+                hashlife_mode = true;
+                pattern_file = next_value();
+            },
+            "-u" | "--unbounded" => {
+                unbounded_mode = true;
+                pattern_file = next_value();
+            },
+            "-b" | "--burn" => {
+                burn_duration = Some(parse_duration(&next_value().unwrap_or_else(|| String::from(DEFAULT_BURN_DURATION)))?);
+            },
+            "-r" | "--stress" => {
+                stress_duration = Some(parse_duration(&next_value().unwrap_or_else(|| String::from(DEFAULT_STRESS_DURATION)))?);
+            },
+            "-s" | "--save-interrupted" => {
+                save_file_name = Some(format_file_name_to_cells(&next_value().unwrap_or_else(|| String::from(DEFAULT_INTERRUPTED_FILE_NAME))));
+            },
+            "-e" | "--trace" => {
+                trace_file_name = Some(format_file_name_to_json(&next_value().unwrap_or_else(|| String::from(DEFAULT_TRACE_FILE_NAME))));
+            },
             _ => {
-                println!("Unknown argument '{}', run with --help for more info.", arg);
-                return Ok(());
+                return Err(format!("Unknown argument '{}', run with --help for more info.", arg).into());
             }
         }
     }
 
+    install_interrupt_handler()?;
+    if trace_file_name.is_some() {
+        config.trace_file = trace_file_name;
+    }
+
     if vis_mode == true {
         let (Width(width), Height(height)) = terminal_size().ok_or("Unable to get terminal window size.")?;
         // Calculation to guarantee an empty column for a \n considering some terminals automatically wrap
@@ -133,35 +773,243 @@ fn run() -> Result<(), String> {
         config.width = divided_width - if width % 2 == 0 { 1 } else { 0 };
         config.height = height as usize;
         config.iterations = usize::MAX;
+        config.duration = None;
+    }
+
+    if hashlife_mode || unbounded_mode {
+        let init_buf = match &pattern_file {
+            Some(file_name) => Pattern::load(file_name)?.cells,
+            None => {
+                let mut init_buf = Buffer::from_value_2d((config.width, config.height), GolCell::Dead);
+                let checkerboard = CheckerboardConf { color_a: GolCell::Dead, color_b: GolCell::Alive, width: config.width };
+                ExecutorSingleThread::<_, _, CheckerboardJobber>::new().compute(Buffer::clone(&init_buf), &mut init_buf.data, checkerboard)?;
+                init_buf
+            },
+        };
+        let pattern_name = pattern_file.as_deref().unwrap_or("a checkerboard buffer");
+        let run_length = config.run_length()?;
+        if hashlife_mode {
+            println!("Launching HashLife for {} of {}", run_length, pattern_name);
+            ExecutorHashLife::new().compute_run(run_length, init_buf, ())?;
+        } else {
+            println!(
+                "Launching unbounded universe for {} of {} with {} thread(s)",
+                run_length,
+                pattern_name,
+                config.thread_count,
+            );
+            let mut universe = ChunkedUniverse::from_buffer(&init_buf, config.chunk_size);
+            match config.jobber {
+                GolJobberKind::Reference => ExecutorUniverse::<GameOfLifeJobber>::new(config.thread_count).compute_run(run_length, &mut universe)?,
+                GolJobberKind::LookupTable => ExecutorUniverse::<LookupTableGolJobber>::new(config.thread_count).compute_run(run_length, &mut universe)?,
+                GolJobberKind::Simd => ExecutorUniverse::<SimdGolJobber>::new(config.thread_count).compute_run(run_length, &mut universe)?,
+            }
+            if is_interrupted() {
+                return Err(AppError::Interrupted);
+            }
+        }
+        return Ok(());
+    }
+
+    let in_buf = Buffer::from_value_2d((config.width, config.height), GolCell::Dead);
+    let exec: ExecutorSingleThread<_, _, CheckerboardJobber> = ExecutorSingleThread::new();
+    let mut init_buf = Buffer::from_value_2d((config.width, config.height), GolCell::Dead);
+    exec.compute(in_buf, &mut init_buf.data, CheckerboardConf { color_a: GolCell::Dead, color_b: GolCell::Alive, width: config.width })?;
+
+    if let Some(file_name) = autotune_file_name {
+        let tuned = autotune(&config, &init_buf)?;
+        // The candidates' timings were cut short, so the fastest one means nothing
+        if is_interrupted() {
+            return Err(AppError::Interrupted);
+        }
+        std::fs::write(&file_name, toml::to_string(&tuned).unwrap()).map_err(|_| "Unable to write to file, exiting.")?;
+        println!("Wrote the fastest configuration to '{}', exiting.", file_name);
+        return Ok(());
     }
 
+    // Cross-checked jobbers must reach the same generation, so they always run the iteration count
+    if cross_check {
+        config.duration = None;
+    }
+    let run_length = match burn_duration.or(stress_duration) {
+        Some(duration) => RunLength::Duration(duration),
+        None => config.run_length()?,
+    };
     println!(
-        "Launching benchmark for {} iterations of a {}x{} buffer with {} thread(s)",
-        config.iterations,
+        "Launching benchmark for {} of a {}x{} buffer with {} thread(s)",
+        run_length,
         config.width,
         config.height,
         if config.parallel_execution { config.thread_count } else { 1 },
     );
 
-    let in_buf = Buffer::from_value_2d((config.width, config.height), GolCell::Dead);
-    let exec: ExecutorSingleThread<_, _, CheckerboardJobber> = ExecutorSingleThread::new();
-    let mut init_buf = Buffer::from_value_2d((config.width, config.height), GolCell::Dead);
-    exec.compute(in_buf, &mut init_buf.data, CheckerboardConf { color_a: GolCell::Dead, color_b: GolCell::Alive, width: config.width });
+    if let Some(instruction_set) = config.instruction_set {
+        SimdGolJobber::force_instruction_set(instruction_set)?;
+    }
+    if config.jobber == GolJobberKind::Simd || cross_check {
+        // Printed so SIMD and scalar scores can be told apart
+        println!("Simd jobber instruction set: {:?}", SimdGolJobber::instruction_set());
+    }
 
-    // Dynamic dispatch adds little to no overhead in this instance since compute_iterations() is called only once
-    let exec_gol: Box<dyn Executor<GolCell, ()>> = match (vis_mode, config.parallel_execution) {
-        (true, _) => Box::new(ExecutorGolVis::new(target_framerate)),
-        (false, true) => Box::new(ExecutorParallel::new::<GameOfLifeJobber>(config.thread_count, config.work_slice_len)),
-        (false, false) => Box::new(ExecutorSingleThread::<GolCell, (), GameOfLifeJobber>::new()),
-    };
+    let pinned_cpus = select_pinned_cpus(&config);
 
-    exec_gol.compute_iterations(config.iterations, init_buf, ());
+    if cross_check && !vis_mode {
+        let decomposition = config.work_decomposition(config.decomposition);
+        let mut reference: Option<Buffer<GolCell>> = None;
+        for kind in [GolJobberKind::Reference, GolJobberKind::LookupTable, GolJobberKind::Simd] {
+            println!("Jobber: {:?}", kind);
+            let exec_gol = build_gol_executor_kind(kind, &config, decomposition, &pinned_cpus)?;
+            let result = exec_gol.compute_iterations(config.iterations, Buffer::clone(&init_buf), ())?;
+            if is_interrupted() {
+                return stop_interrupted(result, save_file_name.as_deref());
+            }
+            match &reference {
+                None => reference = Some(result),
+                Some(reference) => {
+                    if let Some(index) = reference.data.iter().zip(result.data.iter()).position(|(a, b)| a != b) {
+                        return Err(format!("Cross-check failed, {:?} jobber differs from the reference first at cell {:?}", kind, reference.index_to_pos_2d(index)).into());
+                    }
+                },
+            }
+        }
+        println!("Cross-check passed, every jobber produced the same buffer as the reference");
+        return Ok(());
+    }
+
+    if let Some(duration) = stress_duration {
+        // The reference jobbers only know of dead cells beyond the edges
+        if config.padding > 0 && config.boundary == BoundaryMode::Wrap {
+            return Err("The stress test can't verify the wrap boundary, use the fill one or no padding.".into());
+        }
+        println!("Verifying a block of generations out of every {} against the {:?} jobber on a single thread", config.stress_check_interval, config.stress_reference);
+        let exec_gol = build_benchmark_executor(&config, &pinned_cpus)?;
+        let reference = build_stress_reference(&config)?;
+        let outcome = stress(&*exec_gol, &*reference, duration, config.stress_check_interval, Buffer::clone(&init_buf), ())?;
+        println!("Ran {} generations, {} of them verified", outcome.generations, outcome.verified);
+        if let Some(mismatch) = outcome.mismatch {
+            return Err(AppError::HardwareError(mismatch.to_string()));
+        }
+        if is_interrupted() {
+            return stop_interrupted(outcome.buffer, save_file_name.as_deref());
+        }
+        println!("Stress test passed, every verified generation matched the reference");
+        return Ok(());
+    }
+
+    if let Some(duration) = burn_duration {
+        let exec_gol = build_benchmark_executor(&config, &pinned_cpus)?;
+        let (result, samples) = burn(&*exec_gol, duration, Buffer::clone(&init_buf), ())?;
+        print_burn_summary(&samples, config.throttle_threshold);
+        if is_interrupted() {
+            return stop_interrupted(result, save_file_name.as_deref());
+        }
+        return Ok(());
+    }
+
+    let mut runs = vec![(Vec::new(), config.clone())];
+    if compare_decomposition {
+        runs = compare_runs(runs, |config| [DecompositionMode::Slices, DecompositionMode::Tiles]
+            .iter()
+            .map(|&decomposition| (
+                format!("Work decomposition: {:?}", config.work_decomposition(decomposition)),
+                Config { decomposition, ..config.clone() },
+            ))
+            .collect()
+        );
+    }
+    if compare_padding {
+        // Both sides run the reference rules on the same pool and decomposition, one generation per compute,
+        // since the padded jobber has no other variant, so the bounds checks are the only difference
+        runs = compare_runs(runs, |config| [0, usize::max(config.padding, 1)]
+            .iter()
+            .map(|&padding| (
+                match padding {
+                    0 => String::from("Unpadded buffer"),
+                    _ => format!("Padded buffer, {} cell border, {:?} boundary", padding, config.boundary),
+                },
+                Config {
+                    padding,
+                    jobber: GolJobberKind::Reference,
+                    scheduling: Scheduling::Barrier,
+                    generations_per_sync: 1,
+                    skip_inactive: false,
+                    ..config.clone()
+                },
+            ))
+            .collect()
+        );
+    }
+    if compare_layout {
+        // Morton buffers aren't padded, so the padded side of a padding comparison has no Morton run of its own
+        runs = compare_runs(runs, |config| [BufferLayout::RowMajor, BufferLayout::Morton]
+            .iter()
+            .filter(|&&layout| layout == BufferLayout::RowMajor || !(compare_padding && config.padding > 0))
+            .map(|&layout| (
+                format!("Buffer layout: {:?}", layout),
+                match layout {
+                    BufferLayout::RowMajor => config.clone(),
+                    BufferLayout::Morton => Config { layout, padding: 0, ..config.clone() },
+                },
+            ))
+            .collect()
+        );
+    }
+    if compare_queues {
+        runs = compare_runs(runs, |config| [QueueBackend::SegQueue, QueueBackend::ArrayQueue, QueueBackend::MutexCondvar, QueueBackend::Channel]
+            .iter()
+            .map(|&queue_backend| (format!("Job queue: {:?}", queue_backend), Config { queue_backend, ..config.clone() }))
+            .collect()
+        );
+    }
+
+    let mut traces = Vec::new();
+    for (labels, run_config) in runs {
+        for label in &labels {
+            println!("{}", label);
+        }
+
+        // Opened before the executor spawns its threads, so they inherit the counters
+        let mut counters = match run_config.perf_counters && !vis_mode {
+            true => PerfCounters::open().map_err(|reason| println!("Hardware counters unavailable: {}", reason)).ok(),
+            false => None,
+        };
+
+        // Dynamic dispatch adds little to no overhead in this instance since compute_iterations() is called only once
+        let exec_gol: Box<dyn Executor<GolCell, ()>> = match vis_mode {
+            true => Box::new(ExecutorGolVis::new(target_framerate)),
+            false => build_benchmark_executor(&run_config, &pinned_cpus)?,
+        };
+
+        if let Some(counters) = &counters {
+            counters.start();
+        }
+        let (result, completed) = exec_gol.compute_run(run_config.run_length()?, Buffer::clone(&init_buf), ())?;
+        if let Some(counters) = &mut counters {
+            counters.stop().print(completed * run_config.width * run_config.height);
+        }
+        if run_config.trace_file.is_some() {
+            let label = if labels.is_empty() { String::from("Benchmark") } else { labels.join(", ") };
+            traces.push((label, exec_gol.trace()));
+        }
+        if is_interrupted() {
+            if let Some(file_name) = &config.trace_file {
+                save_traces(file_name, &traces)?;
+            }
+            return stop_interrupted(result, save_file_name.as_deref());
+        }
+    }
+    if let Some(file_name) = &config.trace_file {
+        save_traces(file_name, &traces)?;
+    }
 
     return Ok(());
 }
 
 fn main() {
-    if let Err(message) = run() {
-        print!("Fatal Error: {}", message);
+    if let Err(error) = run() {
+        if !matches!(error, AppError::Interrupted) {
+            eprintln!("Fatal Error: {}", error);
+        }
+        std::process::exit(error.exit_code());
     }
 }
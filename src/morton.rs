@@ -0,0 +1,190 @@
+use serde::{Serialize, Deserialize};
+use crate::parallelism::Buffer;
+use crate::layout::LayoutConversion;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferLayout {
+    RowMajor,
+    // Z-order curve, cells close in 2D tend to be close in memory
+    Morton,
+}
+
+// Even bits of a Morton index hold x, odd bits hold y
+const X_BITS: usize = usize::MAX / 3;
+const Y_BITS: usize = X_BITS << 1;
+
+fn spread_bits(value: usize) -> usize {
+    let mut value = value as u64 & 0xFFFF_FFFF;
+    value = (value | (value << 16)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value << 8)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    value = (value | (value << 1)) & 0x5555_5555_5555_5555;
+    value as usize
+}
+
+fn compact_bits(value: usize) -> usize {
+    let mut value = value as u64 & 0x5555_5555_5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value >> 4)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value >> 8)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value >> 16)) & 0x0000_0000_FFFF_FFFF;
+    value as usize
+}
+
+// Maps the cells of a width by height grid along a Z-order curve. Unless both sides are the same power of two,
+// the curve also covers positions outside the grid, which are left as holes in the buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct MortonLayout {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl MortonLayout {
+    pub fn new(dimensions: (usize, usize)) -> Self {
+        Self { width: dimensions.0, height: dimensions.1 }
+    }
+
+    // Morton indices grow with both coordinates, so the last cell has the highest one
+    pub fn len(&self) -> usize {
+        match self.width * self.height {
+            0 => 0,
+            _ => self.pos_to_index((self.width - 1, self.height - 1)) + 1,
+        }
+    }
+
+    pub fn pos_to_index(&self, pos: (usize, usize)) -> usize {
+        spread_bits(pos.0) | (spread_bits(pos.1) << 1)
+    }
+
+    pub fn index_to_pos(&self, index: usize) -> (usize, usize) {
+        (compact_bits(index), compact_bits(index >> 1))
+    }
+
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        pos.0 < self.width && pos.1 < self.height
+    }
+
+    // Whether all eight neighbors of the cell at pos are inside the grid
+    pub fn is_interior(&self, pos: (usize, usize)) -> bool {
+        pos.0 > 0 && pos.1 > 0 && pos.0 + 1 < self.width && pos.1 + 1 < self.height
+    }
+
+    pub fn neighbor(&self, index: usize, pos: (usize, usize), offset: (i32, i32)) -> Option<usize> {
+        let x = pos.0 as i64 + offset.0 as i64;
+        let y = pos.1 as i64 + offset.1 as i64;
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        Some(Self::offset_index(index, offset))
+    }
+
+    // Index at an offset of -1, 0 or 1 on each axis, without converting back and forth, the caller checking the bounds.
+    // Incrementing one coordinate's bits carries through the other's when they're all set, decrementing borrows through them when they're all clear.
+    pub fn offset_index(index: usize, offset: (i32, i32)) -> usize {
+        let x_bits = match offset.0 {
+            1 => ((index | Y_BITS) + 1) & X_BITS,
+            -1 => ((index & X_BITS).wrapping_sub(1)) & X_BITS,
+            _ => index & X_BITS,
+        };
+        let y_bits = match offset.1 {
+            1 => ((index | X_BITS) + 2) & Y_BITS,
+            -1 => ((index & Y_BITS).wrapping_sub(2)) & Y_BITS,
+            _ => index & Y_BITS,
+        };
+        x_bits | y_bits
+    }
+
+    // Aligned squares of side 2^k are contiguous in Morton order, so they make tiles of a single index range
+    pub fn tile_len(tile_side: usize) -> usize {
+        let side = usize::max(tile_side, 1).next_power_of_two();
+        side * side
+    }
+}
+
+impl LayoutConversion for MortonLayout {
+    // The buffers are one dimensional, the 2D dimensions being the layout's
+    fn to_layout<T: Copy>(self, buffer: &Buffer<T>, hole: T) -> Buffer<T> {
+        let mut morton = Buffer::from_value(self.len(), hole);
+        for (index, cell) in buffer.data.iter().enumerate() {
+            morton.data[self.pos_to_index(buffer.index_to_pos_2d(index))] = *cell;
+        }
+        morton
+    }
+
+    fn to_row_major<T: Copy>(self, morton: &Buffer<T>, out: &mut [T]) {
+        for (index, out_cell) in out.iter_mut().enumerate() {
+            *out_cell = morton.data[self.pos_to_index((index % self.width, index / self.width))];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_round_trips() {
+        let layout = MortonLayout::new((1 << 20, 1 << 20));
+        let positions = [(0, 0), (1, 0), (0, 1), (5, 3), (255, 256), (12345, 67890), ((1 << 20) - 1, (1 << 20) - 2)];
+        for pos in positions {
+            assert_eq!(layout.index_to_pos(layout.pos_to_index(pos)), pos);
+        }
+        for index in 0..4096 {
+            assert_eq!(layout.pos_to_index(layout.index_to_pos(index)), index);
+        }
+    }
+
+    #[test]
+    fn indices_interleave_coordinates() {
+        let layout = MortonLayout::new((4, 4));
+        assert_eq!(layout.pos_to_index((1, 0)), 0b01);
+        assert_eq!(layout.pos_to_index((0, 1)), 0b10);
+        assert_eq!(layout.pos_to_index((3, 2)), 0b1101);
+        assert_eq!(layout.len(), 16);
+    }
+
+    // Covers the carries and borrows across power of two boundaries on both axes
+    #[test]
+    fn offset_index_matches_converting() {
+        let layout = MortonLayout::new((64, 64));
+        for y in 1..63 {
+            for x in 1..63 {
+                let index = layout.pos_to_index((x, y));
+                for offset_x in -1..=1 {
+                    for offset_y in -1..=1 {
+                        let neighbor = ((x as i32 + offset_x) as usize, (y as i32 + offset_y) as usize);
+                        assert_eq!(MortonLayout::offset_index(index, (offset_x, offset_y)), layout.pos_to_index(neighbor), "({}, {}) offset by ({}, {})", x, y, offset_x, offset_y);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbors_outside_the_grid_are_none() {
+        let layout = MortonLayout::new((5, 3));
+        let index = layout.pos_to_index((4, 0));
+        assert_eq!(layout.neighbor(index, (4, 0), (1, 0)), None);
+        assert_eq!(layout.neighbor(index, (4, 0), (0, -1)), None);
+        assert_eq!(layout.neighbor(index, (4, 0), (-1, 1)), Some(layout.pos_to_index((3, 1))));
+    }
+
+    #[test]
+    fn conversions_round_trip_around_holes() {
+        let dims = (5, 7);
+        let layout = MortonLayout::new(dims);
+        let mut buffer = Buffer { data: (0..35).collect(), dims: (dims.0, dims.1, 1) };
+        let morton = layout.to_layout(&buffer, usize::MAX);
+        assert_eq!(morton.len(), layout.len());
+        let holes = (0..morton.len()).filter(|index| !layout.contains(layout.index_to_pos(*index))).collect::<Vec<_>>();
+        assert!(!holes.is_empty());
+        assert!(holes.iter().all(|index| morton.data[*index] == usize::MAX));
+        let expected = buffer.data.clone();
+        buffer.data.fill(0);
+        layout.to_row_major(&morton, &mut buffer.data);
+        assert_eq!(buffer.data, expected);
+    }
+}
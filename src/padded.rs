@@ -0,0 +1,90 @@
+use serde::{Serialize, Deserialize};
+use crate::parallelism::Buffer;
+use crate::layout::LayoutConversion;
+
+// What lies beyond the edges of a buffer
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryMode {
+    // A constant value, e.g. dead cells, like the unpadded buffers
+    Fill,
+    // The opposite edge, making the buffer a torus
+    Wrap,
+}
+
+// A width by height grid surrounded by a ghost border of `padding` cells on each side, so reading the neighbors
+// of any cell never goes out of bounds. Positions are logical, (0, 0) being the top left non-ghost cell.
+#[derive(Clone, Copy, Debug)]
+pub struct PaddedLayout {
+    pub width: usize,
+    pub height: usize,
+    pub padding: usize,
+    pub boundary: BoundaryMode,
+}
+
+impl PaddedLayout {
+    pub fn new(dimensions: (usize, usize), padding: usize, boundary: BoundaryMode) -> Self {
+        Self { width: dimensions.0, height: dimensions.1, padding: usize::max(padding, 1), boundary }
+    }
+
+    pub fn stride(&self) -> usize {
+        self.width + 2 * self.padding
+    }
+
+    pub fn padded_dims(&self) -> (usize, usize) {
+        (self.stride(), self.height + 2 * self.padding)
+    }
+
+    // Logical position to physical index, valid from -padding up to dims + padding on both axes
+    pub fn pos_to_index(&self, pos: (isize, isize)) -> usize {
+        let padding = self.padding as isize;
+        ((pos.0 + padding) + (pos.1 + padding) * self.stride() as isize) as usize
+    }
+
+    pub fn index_to_pos(&self, index: usize) -> (isize, isize) {
+        let padding = self.padding as isize;
+        ((index % self.stride()) as isize - padding, (index / self.stride()) as isize - padding)
+    }
+
+    pub fn contains(&self, pos: (isize, isize)) -> bool {
+        pos.0 >= 0 && pos.1 >= 0 && pos.0 < self.width as isize && pos.1 < self.height as isize
+    }
+
+    // Index of the cell whose next state the cell at index takes: itself inside the grid, the opposite edge's cell
+    // for ghost cells of the wrap boundary. Ghost cells of the fill boundary never change, so they have none.
+    pub fn source_index(&self, index: usize) -> Option<usize> {
+        let pos = self.index_to_pos(index);
+        match (self.contains(pos), self.boundary) {
+            (true, _) => Some(index),
+            (false, BoundaryMode::Wrap) => Some(self.pos_to_index((pos.0.rem_euclid(self.width as isize), pos.1.rem_euclid(self.height as isize)))),
+            (false, BoundaryMode::Fill) => None,
+        }
+    }
+}
+
+// The whole padded buffer is split in jobs, those covering the border computing the ghost cells of the next generation
+impl LayoutConversion for PaddedLayout {
+    // Copies the buffer in and fills the ghost border, later generations computing their own border
+    fn to_layout<T: Copy>(self, buffer: &Buffer<T>, fill: T) -> Buffer<T> {
+        let mut padded = Buffer::from_value_2d(self.padded_dims(), fill);
+        for (y, row) in buffer.data.chunks_exact(self.width).enumerate() {
+            let start = self.pos_to_index((0, y as isize));
+            padded.data[start..(start + self.width)].copy_from_slice(row);
+        }
+        if self.boundary == BoundaryMode::Wrap {
+            for index in 0..padded.data.len() {
+                if let Some(source) = self.source_index(index) {
+                    padded.data[index] = padded.data[source];
+                }
+            }
+        }
+        padded
+    }
+
+    fn to_row_major<T: Copy>(self, padded: &Buffer<T>, out: &mut [T]) {
+        for (y, out_row) in out.chunks_exact_mut(self.width).enumerate() {
+            let start = self.pos_to_index((0, y as isize));
+            out_row.copy_from_slice(&padded.data[start..(start + self.width)]);
+        }
+    }
+}
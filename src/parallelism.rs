@@ -1,10 +1,19 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::marker::PhantomData;
-use std::{sync::Arc, thread::JoinHandle};
+use std::ops::Range;
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::{sync::{Arc, Mutex}, thread::JoinHandle};
 use std::thread;
-use crossbeam_queue::SegQueue;
+use crate::topology::{ScopedPin, pinned_worker};
+use crate::queues::{JobQueue, QueueBackend, new_queue};
+use crate::interrupt::is_interrupted;
+use crate::trace::{GenerationSpan, JobSpan, Trace};
 
 #[derive(Clone, Debug)]
 pub struct Buffer<T> {
@@ -101,6 +110,66 @@ impl<T> Buffer<T>
 unsafe impl<T> Send for Buffer<T> {}
 unsafe impl<T> Sync for Buffer<T> {}
 
+#[derive(Debug)]
+pub enum ExecutorError {
+    // A job or a worker thread panicked, or the workers can't be used anymore
+    WorkerFailure(String),
+    // Writing the output failed, e.g. the visualizer's console
+    Io(io::Error),
+    // A buffer's length doesn't match its dimensions, or the input's and output's lengths differ
+    InvalidDimensions(String),
+    // The jobber can't run with a requested option, e.g. temporal blocking on a Morton ordered buffer
+    Unsupported(String),
+}
+
+impl ExecutorError {
+    pub fn from_panic(payload: PanicPayload) -> Self {
+        // panic!() payloads are a &str or a String, depending on whether the message was formatted
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => String::from(*message),
+                Err(_) => String::from("unknown panic payload"),
+            },
+        };
+        ExecutorError::WorkerFailure(message)
+    }
+}
+
+impl fmt::Display for ExecutorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutorError::WorkerFailure(message) => write!(f, "Worker failure: {}", message),
+            ExecutorError::Io(error) => write!(f, "I/O error: {}", error),
+            ExecutorError::InvalidDimensions(message) => write!(f, "Invalid dimensions: {}", message),
+            ExecutorError::Unsupported(message) => write!(f, "Unsupported: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ExecutorError {}
+
+impl From<io::Error> for ExecutorError {
+    fn from(error: io::Error) -> Self {
+        ExecutorError::Io(error)
+    }
+}
+
+// Executors call it before computing, so mismatched buffers are reported rather than panicking somewhere in a worker
+pub fn check_buffers<T>(in_buffer: &Buffer<T>, out_buffer: &[T]) -> Result<(), ExecutorError> {
+    let (width, height, depth) = in_buffer.dims;
+    if width * height * depth == 0 {
+        return Err(ExecutorError::InvalidDimensions(format!("{}x{}x{} buffer has no cells", width, height, depth)));
+    }
+    if width * height * depth != in_buffer.data.len() {
+        return Err(ExecutorError::InvalidDimensions(format!("{}x{}x{} buffer holds {} cells", width, height, depth, in_buffer.data.len())));
+    }
+    if out_buffer.len() != in_buffer.data.len() {
+        return Err(ExecutorError::InvalidDimensions(format!("output buffer holds {} cells, input buffer {}", out_buffer.len(), in_buffer.data.len())));
+    }
+    Ok(())
+}
+
 
 // TODO: Add "Sleep" command that makes the jobber use thread::sleep() instead of thread::yield_now() until job is received
 // Will be useful to not fry the CPU whilst between jobs
@@ -109,35 +178,144 @@ pub enum JobSignal<T, TConf> {
     Death,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum WorkDecomposition {
+    // Linear ranges of work_slice_len items
+    Slices(usize),
+    // Rectangular tiles of (width, height) items, clipped at the buffer's right and bottom edges
+    Tiles(usize, usize),
+}
+
+impl WorkDecomposition {
+    pub fn regions(&self, dims: (usize, usize)) -> Vec<JobRegion> {
+        let buffer_len = dims.0 * dims.1;
+        match *self {
+            WorkDecomposition::Slices(work_slice_len) => (0..buffer_len)
+                .step_by(work_slice_len)
+                .map(|offset| JobRegion::Span {
+                    offset,
+                    count: usize::min(work_slice_len, buffer_len - offset),
+                })
+                .collect(),
+            WorkDecomposition::Tiles(tile_width, tile_height) => (0..dims.1)
+                .step_by(tile_height)
+                .flat_map(|y| (0..dims.0)
+                    .step_by(tile_width)
+                    .map(move |x| JobRegion::Tile {
+                        offset: x + y * dims.0,
+                        width: usize::min(tile_width, dims.0 - x),
+                        height: usize::min(tile_height, dims.1 - y),
+                        stride: dims.0,
+                    })
+                )
+                .collect(),
+        }
+    }
+
+    pub fn max_region_len(&self) -> usize {
+        match *self {
+            WorkDecomposition::Slices(work_slice_len) => work_slice_len,
+            WorkDecomposition::Tiles(tile_width, tile_height) => tile_width * tile_height,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JobRegion {
+    Span {
+        offset: usize,
+        count: usize,
+    },
+    Tile {
+        offset: usize,
+        width: usize,
+        height: usize,
+        stride: usize,
+    },
+}
+
+impl JobRegion {
+    pub fn offset(&self) -> usize {
+        match *self {
+            JobRegion::Span { offset, .. } | JobRegion::Tile { offset, .. } => offset,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match *self {
+            JobRegion::Span { count, .. } => count,
+            JobRegion::Tile { width, height, .. } => width * height,
+        }
+    }
+
+    // (x, y, width, height) of the smallest rectangle containing the region
+    pub fn bounding_box(&self, buffer_width: usize) -> (usize, usize, usize, usize) {
+        match *self {
+            JobRegion::Span { offset, count } => {
+                let (first_x, first_y) = (offset % buffer_width, offset / buffer_width);
+                let (last_x, last_y) = ((offset + count - 1) % buffer_width, (offset + count - 1) / buffer_width);
+                match first_y == last_y {
+                    true => (first_x, first_y, last_x - first_x + 1, 1),
+                    false => (0, first_y, buffer_width, last_y - first_y + 1),
+                }
+            },
+            JobRegion::Tile { offset, width, height, .. } => (offset % buffer_width, offset / buffer_width, width, height),
+        }
+    }
+
+    // Contiguous index ranges of the region, in the order its items are laid out in a job's out_buffer
+    pub fn rows(&self) -> impl Iterator<Item = std::ops::Range<usize>> {
+        let (offset, width, height, stride) = match *self {
+            JobRegion::Span { offset, count } => (offset, count, 1, count),
+            JobRegion::Tile { offset, width, height, stride } => (offset, width, height, stride),
+        };
+        (0..height).map(move |row| {
+            let row_offset = offset + row * stride;
+            row_offset..(row_offset + width)
+        })
+    }
+}
+
 pub struct JobDescriptor<T, TConf> {
     buffer: Arc<Buffer<T>>,
     conf: Arc<TConf>,
 
-    offset: usize,
-    count: usize,
+    region: JobRegion,
+    region_index: usize,
+    // Generations to advance the region by, more than 1 means computing a halo of that width around it
+    generations: usize,
+    // Whether the worker should compare its output against the input, otherwise it's assumed changed
+    track_changes: bool,
     out_buffer: Vec<T>,
 }
 
+pub type PanicPayload = Box<dyn Any + Send>;
+
+// Either a job's result, or what the jobber panicked with
+pub type JobOutcome<T> = Result<JobResult<T>, PanicPayload>;
+
 pub struct JobResult<T> {
     buffer: Vec<T>,
-    count: usize,
+    changed: bool,
 
-    offset: usize,
+    region: JobRegion,
+    region_index: usize,
 }
 
 impl<T, TConf> From<JobDescriptor<T, TConf>> for JobResult<T> {
     fn from(descriptor: JobDescriptor<T, TConf>) -> Self {
         Self {
             buffer: descriptor.out_buffer,
-            count: descriptor.count,
-            offset: descriptor.offset,
+            changed: true,
+            region: descriptor.region,
+            region_index: descriptor.region_index,
         }
     }
 }
 
 impl<T> Ord for JobResult<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.offset.cmp(&other.offset)
+        self.region.offset().cmp(&other.region.offset())
     }
 }
 
@@ -149,7 +327,7 @@ impl<T> PartialOrd for JobResult<T> {
 
 impl<T> PartialEq for JobResult<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.offset.eq(&other.offset)
+        self.region.offset().eq(&other.region.offset())
     }
 }
 
@@ -159,28 +337,133 @@ pub trait Jobber<T, TConf>
     where T: Copy
 {
     fn job_loop(
-        job_queue: Arc<SegQueue<JobSignal<T, TConf>>>,
-        res_queue: Arc<SegQueue<JobResult<T>>>,
-    ) -> () {
+        job_queue: Arc<dyn JobQueue<JobSignal<T, TConf>>>,
+        res_queue: Arc<dyn JobQueue<JobOutcome<T>>>,
+        stats: Arc<WorkerStats>,
+    ) -> ()
+        where T: PartialEq
+    {
         loop {
-            if let Some(signal) = job_queue.pop() {
-                match signal {
-                    JobSignal::Work(mut job) => {
-                        job.out_buffer.clear();
-                        for index in (job.offset)..(job.offset + job.count) {
-                            job.out_buffer.push(Self::process_job(&job.buffer, index, &*job.conf));
-                        }
-                        res_queue.push(job.into());
-                    },
-                    JobSignal::Death => return,
-                }
-            } else {
-                thread::yield_now();
+            let wait_start = Instant::now();
+            let signal = job_queue.pop_wait();
+            let job_start = Instant::now();
+            stats.record_idle(job_start - wait_start);
+            match signal {
+                // A panicking job is reported like any other, so the executor isn't left waiting for its result
+                JobSignal::Work(job) => {
+                    let (region, generations) = (job.region, job.generations);
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| Self::run_job(job)));
+                    stats.record_job(&region, generations, job_start, Instant::now());
+                    res_queue.push(outcome);
+                },
+                JobSignal::Death => return,
             }
         }
     }
 
+    fn run_job(mut job: JobDescriptor<T, TConf>) -> JobResult<T>
+        where T: PartialEq
+    {
+        job.out_buffer.clear();
+        if job.generations > 1 {
+            Self::process_region_generations(&job.buffer, job.region, &*job.conf, job.generations, &mut job.out_buffer);
+        } else {
+            for row in job.region.rows() {
+                // Seeded with the input so the span can be written in place
+                let row_start = job.out_buffer.len();
+                job.out_buffer.extend_from_slice(&job.buffer.data[row.clone()]);
+                Self::process_span(&job.buffer, row, &*job.conf, &mut job.out_buffer[row_start..]);
+            }
+        }
+        let changed = !job.track_changes || job.region
+            .rows()
+            .scan(0, |compared, row| {
+                let row_len = row.len();
+                *compared += row_len;
+                Some(job.buffer.data[row] != job.out_buffer[(*compared - row_len)..*compared])
+            })
+            .any(|row_changed| row_changed);
+        let mut result: JobResult<T> = job.into();
+        result.changed = changed;
+        result
+    }
+
+    // Temporal blocking: copies the region plus a halo as wide as the generation count into a local buffer,
+    // and advances it that many generations on its own. Errors from the halo's missing outer neighbors
+    // travel inwards one cell per generation, so they never reach the region itself.
+    // Only valid for jobbers that read their immediate neighbors and not their absolute position.
+    fn process_region_generations(buffer: &Buffer<T>, region: JobRegion, conf: &TConf, generations: usize, out_buffer: &mut Vec<T>) {
+        let (x, y, width, height) = region.bounding_box(buffer.dims.0);
+        // Halos are clipped at the buffer's edges, which then behave the same locally and globally
+        let local_x = x.saturating_sub(generations);
+        let local_y = y.saturating_sub(generations);
+        let local_width = usize::min(x + width + generations, buffer.dims.0) - local_x;
+        let local_height = usize::min(y + height + generations, buffer.dims.1) - local_y;
+
+        let mut local_data = Vec::with_capacity(local_width * local_height);
+        for row in local_y..(local_y + local_height) {
+            let row_offset = local_x + row * buffer.dims.0;
+            local_data.extend_from_slice(&buffer.data[row_offset..(row_offset + local_width)]);
+        }
+        let mut front = Buffer {
+            data: local_data.into_boxed_slice(),
+            dims: (local_width, local_height, 1),
+        };
+        let mut back = Buffer::clone(&front);
+        for _ in 0..generations {
+            Self::process_span(&front, 0..front.len(), conf, &mut back.data);
+            std::mem::swap(&mut front, &mut back);
+        }
+
+        for row in region.rows() {
+            let (row_x, row_y) = buffer.index_to_pos_2d(row.start);
+            let local_offset = (row_x - local_x) + (row_y - local_y) * local_width;
+            out_buffer.extend_from_slice(&front.data[local_offset..(local_offset + row.len())]);
+        }
+    }
+
+    // Processes a contiguous range of indices, which may span several rows, into out_span (same length as indices).
+    // Jobbers can override it to avoid recomputing positions and neighbors for each index.
+    fn process_span(buffer: &Buffer<T>, indices: Range<usize>, conf: &TConf, out_span: &mut [T]) {
+        for (out, index) in out_span.iter_mut().zip(indices) {
+            *out = Self::process_job(buffer, index, conf);
+        }
+    }
+
     fn process_job(buffer: &Buffer<T>, index: usize, conf: &TConf) -> T;
+
+    // Regions the buffer is split in for a compute, jobbers of buffers with cells that never change may leave them out,
+    // ExecutorParallel keeping their value from the output buffer
+    fn regions(decomposition: &WorkDecomposition, dims: (usize, usize), _conf: &TConf) -> Vec<JobRegion> {
+        decomposition.regions(dims)
+    }
+
+    // Whether a region can be computed from a row-major copy of its bounding box and a halo, as temporal blocking
+    // and wavefront scheduling do, which needs the buffer's dimensions to be the grid's
+    fn supports_region_generations() -> bool {
+        true
+    }
+
+    // Whether a region's next state only depends on the regions around its bounding box, which inactive skipping assumes
+    fn supports_inactive_skipping() -> bool {
+        true
+    }
+}
+
+// How long a run lasts, a fixed count of generations or as many as fit in a wall-clock budget
+#[derive(Clone, Copy, Debug)]
+pub enum RunLength {
+    Iterations(usize),
+    Duration(Duration),
+}
+
+impl fmt::Display for RunLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunLength::Iterations(iterations) => write!(f, "{} iterations", iterations),
+            RunLength::Duration(duration) => write!(f, "{:?}", duration),
+        }
+    }
 }
 
 pub trait Executor<T, TConf>
@@ -188,155 +471,679 @@ pub trait Executor<T, TConf>
         T: Clone,
         TConf: Clone,
 {
-    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Buffer<T>;
+    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Result<Buffer<T>, ExecutorError>;
+
+    // Executor specific statistics, printed after the timings
+    fn print_stats(&self) { }
+
+    // What the executor recorded of its threads' work if it supports tracing and it was enabled, empty otherwise
+    fn trace(&self) -> Trace {
+        Trace::default()
+    }
+
+    // Executors able to advance several generations per synchronization override these two
+    fn max_generations_per_compute(&self) -> usize {
+        1
+    }
+
+    // Also returns how many generations out_buffer is ahead of in_buffer, fewer than requested only if interrupted
+    // or past the deadline, which executors computing several generations per call check in between generations
+    fn compute_generations(
+        &self,
+        in_buffer: Buffer<T>,
+        out_buffer: &mut [T],
+        conf: TConf,
+        generations: usize,
+        _deadline: Option<Instant>,
+    ) -> Result<(Buffer<T>, usize), ExecutorError> {
+        debug_assert_eq!(generations, 1);
+        Ok((self.compute(in_buffer, out_buffer, conf)?, generations))
+    }
+
+    fn compute_iterations(&self, iterations: usize, buffer: Buffer<T>, conf: TConf) -> Result<Buffer<T>, ExecutorError> {
+        Ok(self.compute_run(RunLength::Iterations(iterations), buffer, conf)?.0)
+    }
 
-    fn compute_iterations(&self, iterations: usize, mut buffer: Buffer<T>, conf: TConf) -> Buffer<T> {
+    // Stops early when interrupted, the timings and the returned buffer then cover the generations completed so far,
+    // whose count is returned with the buffer
+    fn compute_run(&self, length: RunLength, buffer: Buffer<T>, conf: TConf) -> Result<(Buffer<T>, usize), ExecutorError> {
+        let now = Instant::now();
+        let (buffer, completed) = self.advance(length, buffer, conf)?;
+        if is_interrupted() {
+            println!("Interrupted after {} iterations", completed);
+        } else if let RunLength::Duration(duration) = length {
+            println!("Completed {} iterations in the {:?} budget", completed, duration);
+        }
+        let elapsed = now.elapsed().as_millis() as f32 / 1000_f32;
+        // Nothing to average over when interrupted before the first generation
+        match completed {
+            0 => println!("Time elapsed: {}s, no iterations completed", elapsed),
+            _ => {
+                let elapsed_per_iter = elapsed / completed as f32;
+                let iter_per_sec = completed as f32 / f32::max(elapsed, f32::EPSILON);
+                println!("Time elapsed: {}s, {}s per iteration, {} iterations per second", elapsed, elapsed_per_iter, iter_per_sec);
+            },
+        }
+        self.print_stats();
+        return Ok((buffer, completed));
+    }
+
+    // compute_run without the timings, also returning how many generations were completed
+    fn advance(&self, length: RunLength, mut buffer: Buffer<T>, conf: TConf) -> Result<(Buffer<T>, usize), ExecutorError> {
         let mut toggle = true;
         let mut buffer2 = Buffer::clone(&buffer);
-        let now = Instant::now();
-        for _ in 0..iterations {
+        let max_generations = usize::max(self.max_generations_per_compute(), 1);
+        let mut completed = 0;
+        let (iterations, deadline) = match length {
+            RunLength::Iterations(iterations) => (iterations, None),
+            RunLength::Duration(duration) => (usize::MAX, Some(Instant::now() + duration)),
+        };
+        while completed < iterations && !is_interrupted() && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            let generations = usize::min(max_generations, iterations - completed);
+            let computed;
             match toggle {
                 true => {
-                    buffer = self.compute(buffer, &mut buffer2.data, TConf::clone(&conf));
+                    (buffer, computed) = self.compute_generations(buffer, &mut buffer2.data, TConf::clone(&conf), generations, deadline)?;
                 },
                 false => {
-                    buffer2 = self.compute(buffer2, &mut buffer.data, TConf::clone(&conf));
+                    (buffer2, computed) = self.compute_generations(buffer2, &mut buffer.data, TConf::clone(&conf), generations, deadline)?;
                 },
             };
             toggle = !toggle;
+            completed += computed;
         }
-        let elapsed = now.elapsed().as_millis() as f32 / 1000_f32;
-        let elapsed_per_iter = elapsed / iterations as f32;
-        let iter_per_sec = 1_f32 / elapsed_per_iter;
-        println!("Time elapsed: {}s, {}s per iteration, {} iterations per second", elapsed, elapsed_per_iter, iter_per_sec);
-        return match toggle {
+        let buffer = match toggle {
             true => buffer,
             false => buffer2,
         };
+        Ok((buffer, completed))
+    }
+}
+
+type JobRunner<T, TConf> = fn(JobDescriptor<T, TConf>) -> JobResult<T>;
+type RegionBuilder<TConf> = fn(&WorkDecomposition, (usize, usize), &TConf) -> Vec<JobRegion>;
+
+// Work done by one thread of an ExecutorParallel over the run, updated by the thread itself.
+// Idle time is spent waiting for a job, spinning or sleeping depending on the queue backend, a wait still in progress
+// when the stats are printed isn't counted.
+pub struct WorkerStats {
+    // Track of the thread in traces
+    thread: usize,
+    jobs: AtomicUsize,
+    cells: AtomicUsize,
+    busy_nanos: AtomicU64,
+    idle_nanos: AtomicU64,
+    // Every job's span once tracing is enabled
+    job_spans: Mutex<Option<Vec<JobSpan>>>,
+}
+
+impl WorkerStats {
+    fn new(thread: usize) -> Self {
+        Self {
+            thread,
+            jobs: AtomicUsize::new(0),
+            cells: AtomicUsize::new(0),
+            busy_nanos: AtomicU64::new(0),
+            idle_nanos: AtomicU64::new(0),
+            job_spans: Mutex::new(None),
+        }
+    }
+
+    fn record_job(&self, region: &JobRegion, generations: usize, start: Instant, end: Instant) {
+        self.jobs.fetch_add(1, AtomicOrdering::Relaxed);
+        self.cells.fetch_add(region.len() * generations, AtomicOrdering::Relaxed);
+        self.busy_nanos.fetch_add((end - start).as_nanos() as u64, AtomicOrdering::Relaxed);
+        if let Some(spans) = self.job_spans.lock().unwrap().as_mut() {
+            spans.push(JobSpan { thread: self.thread, offset: region.offset(), count: region.len(), generations, start, end });
+        }
+    }
+
+    fn record_idle(&self, idle: Duration) {
+        self.idle_nanos.fetch_add(idle.as_nanos() as u64, AtomicOrdering::Relaxed);
+    }
+
+    fn busy(&self) -> Duration {
+        Duration::from_nanos(self.busy_nanos.load(AtomicOrdering::Relaxed))
+    }
+
+    fn idle(&self) -> Duration {
+        Duration::from_nanos(self.idle_nanos.load(AtomicOrdering::Relaxed))
+    }
+
+    fn print(&self, name: &str, include_idle: bool) {
+        let (busy, idle) = (self.busy().as_secs_f32(), self.idle().as_secs_f32());
+        let idle_part = match include_idle {
+            true => format!(", idle {:.3}s ({:.1}% busy)", idle, 100_f32 * busy / f32::max(busy + idle, f32::EPSILON)),
+            false => String::new(),
+        };
+        println!(
+            "{}: {} jobs, {} cells, busy {:.3}s{}",
+            name,
+            self.jobs.load(AtomicOrdering::Relaxed),
+            self.cells.load(AtomicOrdering::Relaxed),
+            busy,
+            idle_part,
+        );
+    }
+}
+
+// How much longer than the average the busiest thread worked, as a percentage, 0 when perfectly balanced
+pub fn load_imbalance(busy_times: &[Duration]) -> f32 {
+    let max = busy_times.iter().max().copied().unwrap_or_default().as_secs_f32();
+    let mean = busy_times.iter().sum::<Duration>().as_secs_f32() / usize::max(busy_times.len(), 1) as f32;
+    match mean > 0_f32 {
+        true => 100_f32 * (max / mean - 1_f32),
+        false => 0_f32,
     }
 }
 
 pub struct ExecutorParallel<T, TConf>
 {
-    job_queue: Arc<SegQueue<JobSignal<T, TConf>>>,
-    res_queue: Arc<SegQueue<JobResult<T>>>,
-    threads: Vec<JoinHandle<()>>,
-    work_slice_len: usize,
+    job_queue: Arc<dyn JobQueue<JobSignal<T, TConf>>>,
+    res_queue: Arc<dyn JobQueue<JobOutcome<T>>>,
+    threads: RefCell<Vec<JoinHandle<()>>>,
+    // Set once a worker panicked or died, after which the pool is shut down
+    is_shut_down: Cell<bool>,
+    decomposition: WorkDecomposition,
+    generations_per_sync: usize,
     slices: RefCell<Vec<Vec<T>>>,
+    layout: RefCell<RegionLayout>,
+    skip_inactive: bool,
+    skip_stats: Cell<(usize, usize)>,
+    phase_timing: bool,
+    phase_times: Cell<PhaseTimes>,
+    report_worker_stats: bool,
+    worker_stats: Vec<Arc<WorkerStats>>,
+    // Only busy time is tracked for the main thread, it's never idle since it dispatches when not running jobs
+    main_thread_stats: WorkerStats,
+    // Generations computed so far, and the span of each compute once tracing is enabled
+    generation: Cell<usize>,
+    generation_spans: RefCell<Option<Vec<GenerationSpan>>>,
+    // Set when the dispatching thread runs jobs itself while waiting for results, and the CPU it's pinned to meanwhile
+    main_thread_job: Option<JobRunner<T, TConf>>,
+    main_thread_cpu: Cell<Option<usize>>,
+    region_builder: RegionBuilder<TConf>,
+    // What the jobber supports, checked by the builder methods enabling it
+    jobber_name: &'static str,
+    supports_region_generations: bool,
+    supports_inactive_skipping: bool,
+}
+
+// Time the dispatching thread spent in each phase of the computes, summed over the run.
+// The workers compute while it enqueues and waits. If it works too, the jobs it runs meanwhile are
+// taken out of both phases and reported on their own, so they don't pass for dispatching overhead.
+#[derive(Clone, Copy, Default)]
+struct PhaseTimes {
+    computes: usize,
+    // Updating the regions and picking the active ones
+    setup: Duration,
+    enqueue: Duration,
+    wait: Duration,
+    running_jobs: Duration,
+    copy: Duration,
+    unwrap: Duration,
+}
+
+impl PhaseTimes {
+    fn print(&self, main_thread_works: bool) {
+        let mut phases = vec![
+            ("setup", self.setup),
+            ("enqueuing jobs", self.enqueue),
+            ("waiting for results", self.wait),
+        ];
+        if main_thread_works {
+            phases.push(("running jobs", self.running_jobs));
+        }
+        phases.extend([
+            ("copying slices back", self.copy),
+            ("unwrapping the buffer", self.unwrap),
+        ]);
+        let total: Duration = phases.iter().map(|(_, time)| *time).sum();
+        println!(
+            "Phase timing over {} computes, {:.1}µs each:",
+            self.computes,
+            total.as_secs_f64() * 1_000_000_f64 / usize::max(self.computes, 1) as f64,
+        );
+        for (name, time) in phases {
+            println!("    {}: {:.2}ms ({:.2}%)", name, time.as_secs_f64() * 1000_f64, 100_f64 * time.as_secs_f64() / f64::max(total.as_secs_f64(), f64::MIN_POSITIVE));
+        }
+    }
+}
+
+// Regions of the last computed buffer size, with the change tracking used to skip inactive ones
+#[derive(Default)]
+struct RegionLayout {
+    dims: (usize, usize),
+    generations: usize,
+    regions: Vec<JobRegion>,
+    // Indices of the regions each region's next state depends on, itself included
+    dependencies: Vec<Vec<usize>>,
+    // Whether each region changed during the last compute, meaningless unless the next in_buffer is the last out_buffer
+    changed: Vec<bool>,
+    last_out_buffer: usize,
+}
+
+impl RegionLayout {
+    fn update(&mut self, regions: Vec<JobRegion>, dims: (usize, usize), generations: usize, track_dependencies: bool) {
+        if self.dims == dims && self.generations == generations && self.regions == regions && !self.regions.is_empty() {
+            return;
+        }
+        self.dims = dims;
+        self.generations = generations;
+        self.regions = regions;
+        self.changed = vec![true; self.regions.len()];
+        self.dependencies = Vec::new();
+        if track_dependencies {
+            // A cell depends on the cells up to a distance of one per generation
+            self.dependencies = region_dependencies(&self.regions, dims.0, generations);
+        }
+    }
+}
+
+// Indices of the regions whose bounding boxes are within distance of each region's, itself included
+pub fn region_dependencies(regions: &[JobRegion], buffer_width: usize, distance: usize) -> Vec<Vec<usize>> {
+    let boxes: Vec<_> = regions.iter().map(|region| region.bounding_box(buffer_width)).collect();
+    boxes
+        .iter()
+        .map(|&(x, y, width, height)| boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, &(other_x, other_y, other_width, other_height))| {
+                other_x < x + width + distance && x < other_x + other_width + distance
+                    && other_y < y + height + distance && y < other_y + other_height + distance
+            })
+            .map(|(index, _)| index)
+            .collect()
+        )
+        .collect()
 }
 
 impl<T, TConf> ExecutorParallel<T, TConf>
     where
-        T: 'static + Send + Sync + Copy,
+        T: 'static + Send + Sync + Copy + PartialEq,
         TConf: 'static + Send + Sync,
 {
+    #[allow(dead_code)]
     pub fn new<TJobber: Jobber<T, TConf>>(thread_count: usize, work_slice_len: usize) -> Self {
+        Self::new_pinned::<TJobber>(thread_count, WorkDecomposition::Slices(work_slice_len), &[], false, QueueBackend::SegQueue)
+    }
+
+    // Worker i is pinned to cpus[i % cpus.len()], an empty list leaves scheduling to the OS.
+    // When the main thread works, it counts as one of the threads and the workers start at cpus[1], leaving cpus[0] to it,
+    // which it's pinned to during each compute.
+    pub fn new_pinned<TJobber: Jobber<T, TConf>>(
+        thread_count: usize,
+        decomposition: WorkDecomposition,
+        cpus: &[usize],
+        main_thread_works: bool,
+        queue_backend: QueueBackend,
+    ) -> Self {
         let thread_count = usize::max(thread_count, 1);
-        let work_slice_len = usize::max(work_slice_len, 1);
+        let (worker_count, first_cpu) = match main_thread_works {
+            true => (thread_count - 1, 1),
+            false => (thread_count, 0),
+        };
+        let decomposition = match decomposition {
+            WorkDecomposition::Slices(work_slice_len) => WorkDecomposition::Slices(usize::max(work_slice_len, 1)),
+            WorkDecomposition::Tiles(tile_width, tile_height) => WorkDecomposition::Tiles(usize::max(tile_width, 1), usize::max(tile_height, 1)),
+        };
 
-        let job_queue = Arc::new(SegQueue::new());
-        let res_queue = Arc::new(SegQueue::new());
+        let job_queue = new_queue(queue_backend);
+        let res_queue = new_queue(queue_backend);
 
-        let mut threads: Vec<JoinHandle<()>> = Vec::with_capacity(thread_count);
-        for _ in 0..thread_count {
+        let worker_stats: Vec<Arc<WorkerStats>> = (1..=worker_count).map(|thread| Arc::new(WorkerStats::new(thread))).collect();
+        let mut threads: Vec<JoinHandle<()>> = Vec::with_capacity(worker_count);
+        for (i, stats) in worker_stats.iter().enumerate() {
             let job_queue_clone = Arc::clone(&job_queue);
             let res_queue_clone = Arc::clone(&res_queue);
-            threads.push(thread::spawn(move || {
-                TJobber::job_loop(job_queue_clone, res_queue_clone);
-            }));
+            let stats_clone = Arc::clone(stats);
+            threads.push(thread::spawn(pinned_worker(cpus, first_cpu + i, move || {
+                TJobber::job_loop(job_queue_clone, res_queue_clone, stats_clone);
+            })));
         }
 
         Self {
             job_queue,
             res_queue,
-            threads,
-            work_slice_len,
+            threads: RefCell::new(threads),
+            is_shut_down: Cell::new(false),
+            decomposition,
+            generations_per_sync: 1,
             slices: RefCell::new(Vec::new()),
+            layout: RefCell::new(RegionLayout::default()),
+            skip_inactive: false,
+            skip_stats: Cell::new((0, 0)),
+            phase_timing: false,
+            phase_times: Cell::new(PhaseTimes::default()),
+            report_worker_stats: false,
+            worker_stats,
+            main_thread_stats: WorkerStats::new(0),
+            generation: Cell::new(0),
+            generation_spans: RefCell::new(None),
+            main_thread_job: if main_thread_works { Some(TJobber::run_job) } else { None },
+            main_thread_cpu: Cell::new(if main_thread_works { cpus.first().copied() } else { None }),
+            region_builder: TJobber::regions,
+            jobber_name: std::any::type_name::<TJobber>(),
+            supports_region_generations: TJobber::supports_region_generations(),
+            supports_inactive_skipping: TJobber::supports_inactive_skipping(),
         }
     }
 
+    // Regions whose dependencies didn't change during the last compute are copied over instead of being computed,
+    // which assumes each compute continues the simulation of the previous one (checked by the buffer addresses)
+    pub fn with_inactive_skipping(mut self, skip_inactive: bool) -> Result<Self, ExecutorError> {
+        if skip_inactive && !self.supports_inactive_skipping {
+            return Err(ExecutorError::Unsupported(format!("{} doesn't support inactive skipping", self.jobber_name)));
+        }
+        self.skip_inactive = skip_inactive;
+        Ok(self)
+    }
+
+    // Prints the time spent in each phase of the computes with the stats, they're measured either way
+    pub fn with_phase_timing(mut self, phase_timing: bool) -> Self {
+        self.phase_timing = phase_timing;
+        self
+    }
+
+    // Prints the jobs, cells, busy and idle time of each thread with the stats, and how unevenly the work was spread
+    pub fn with_worker_stats(mut self, report_worker_stats: bool) -> Self {
+        self.report_worker_stats = report_worker_stats;
+        self
+    }
+
+    // Records the span of every job and compute, for trace()
+    pub fn with_tracing(self, tracing: bool) -> Self {
+        if tracing {
+            for stats in self.worker_stats.iter().map(|stats| &**stats).chain([&self.main_thread_stats]) {
+                *stats.job_spans.lock().unwrap() = Some(Vec::new());
+            }
+            *self.generation_spans.borrow_mut() = Some(Vec::new());
+        }
+        self
+    }
+
+    fn print_worker_stats(&self) {
+        for (index, stats) in self.worker_stats.iter().enumerate() {
+            stats.print(&format!("Worker {}", index), true);
+        }
+        let mut busy_times: Vec<Duration> = self.worker_stats.iter().map(|stats| stats.busy()).collect();
+        if self.main_thread_job.is_some() {
+            self.main_thread_stats.print("Main thread", false);
+            busy_times.push(self.main_thread_stats.busy());
+        }
+        println!("Load imbalance: {:.1}% (busiest thread's busy time over the average)", load_imbalance(&busy_times));
+    }
+
+    // Temporal blocking, each job advances its region several generations before the threads synchronize,
+    // at the cost of redundantly computing a halo of that width around it
+    pub fn with_generations_per_sync(mut self, generations_per_sync: usize) -> Result<Self, ExecutorError> {
+        if generations_per_sync > 1 && !self.supports_region_generations {
+            return Err(ExecutorError::Unsupported(format!("{} doesn't support temporal blocking", self.jobber_name)));
+        }
+        self.generations_per_sync = usize::max(generations_per_sync, 1);
+        Ok(self)
+    }
+
     pub fn get_slice(&self) -> Vec<T> {
-        return self.slices.borrow_mut().pop().unwrap_or(Vec::with_capacity(self.work_slice_len));
+        return self.slices.borrow_mut().pop().unwrap_or_else(|| Vec::with_capacity(self.decomposition.max_region_len()));
     }
 
     pub fn push_slice(&self, slice: Vec<T>) {
         return self.slices.borrow_mut().push(slice);
     }
+
+    // The main thread's previous affinity is restored once the pin is dropped, giving up on pinning it after the first failure
+    fn pin_main_thread(&self) -> Option<ScopedPin> {
+        let cpu = self.main_thread_cpu.get()?;
+        let pin = ScopedPin::pin_current_thread(cpu);
+        if pin.is_none() {
+            eprintln!("Unable to pin main thread to cpu{}", cpu);
+            self.main_thread_cpu.set(None);
+        }
+        pin
+    }
+
+    // Takes a finished result, or runs a queued job if the main thread works, returns whether there was either.
+    // Failed jobs and dead workers are reported as an error.
+    fn collect_result(&self, slices: &mut Vec<JobResult<T>>) -> Result<bool, PanicPayload> {
+        if let Some(outcome) = self.res_queue.pop() {
+            slices.push(outcome?);
+            return Ok(true);
+        }
+        let main_thread_outcome = self.main_thread_job.and_then(|run_job| match self.job_queue.pop() {
+            Some(JobSignal::Work(job)) => {
+                let (region, generations) = (job.region, job.generations);
+                let job_start = Instant::now();
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| run_job(job)));
+                self.main_thread_stats.record_job(&region, generations, job_start, Instant::now());
+                Some(outcome)
+            },
+            // Death signals are only sent on drop or shut down, which can't happen during a compute
+            Some(JobSignal::Death) => unreachable!(),
+            None => None,
+        });
+        if let Some(outcome) = main_thread_outcome {
+            slices.push(outcome?);
+            return Ok(true);
+        }
+        // Jobbers' panics are caught, but a worker could still die outside of a job
+        let dead_worker = self.threads.borrow().iter().position(|handle| handle.is_finished());
+        if let Some(index) = dead_worker {
+            let handle = self.threads.borrow_mut().remove(index);
+            return match handle.join() {
+                Err(payload) => Err(payload),
+                Ok(()) => Err(Box::new("Worker thread exited unexpectedly")),
+            };
+        }
+        Ok(false)
+    }
 }
 
 impl<T, TConf> Executor<T, TConf> for ExecutorParallel<T, TConf>
     where
-        T: 'static + Send + Sync + Copy,
+        T: 'static + Send + Sync + Copy + PartialEq,
         TConf: 'static + Send + Sync + Clone,
 {
-    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Buffer<T> {
-        let buffer_len = in_buffer.len();
-        let slice_count = buffer_len / self.work_slice_len;
-        let slice_leftover = buffer_len % self.work_slice_len;
+    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Result<Buffer<T>, ExecutorError> {
+        Ok(self.compute_generations(in_buffer, out_buffer, conf, 1, None)?.0)
+    }
+
+    fn max_generations_per_compute(&self) -> usize {
+        self.generations_per_sync
+    }
+
+    fn print_stats(&self) {
+        if self.skip_inactive {
+            let (skipped, total) = self.skip_stats.get();
+            println!("Skipped {} of {} regions as inactive ({:.2}%)", skipped, total, 100_f32 * skipped as f32 / usize::max(total, 1) as f32);
+        }
+        if self.phase_timing {
+            self.phase_times.get().print(self.main_thread_job.is_some());
+        }
+        if self.report_worker_stats {
+            self.print_worker_stats();
+        }
+    }
+
+    fn trace(&self) -> Trace {
+        let mut thread_names = vec![String::from(match self.main_thread_job {
+            Some(_) => "Main thread",
+            None => "Dispatching thread",
+        })];
+        thread_names.extend((0..self.worker_stats.len()).map(|index| format!("Worker {}", index)));
+        let mut jobs = Vec::new();
+        for stats in self.worker_stats.iter().map(|stats| &**stats).chain([&self.main_thread_stats]) {
+            jobs.extend(stats.job_spans.lock().unwrap().take().unwrap_or_default());
+        }
+        Trace {
+            thread_names,
+            jobs,
+            generations: self.generation_spans.borrow_mut().take().unwrap_or_default(),
+        }
+    }
+
+    // Temporal blocking keeps generations_per_sync small, so the deadline is only checked in between computes
+    fn compute_generations(
+        &self,
+        in_buffer: Buffer<T>,
+        out_buffer: &mut [T],
+        conf: TConf,
+        generations: usize,
+        _deadline: Option<Instant>,
+    ) -> Result<(Buffer<T>, usize), ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
+        if self.is_shut_down.get() {
+            return Err(ExecutorError::WorkerFailure(String::from("the worker pool was shut down after a previous failure")));
+        }
+
+        let _pin = self.pin_main_thread();
+        let setup_start = Instant::now();
+        let mut layout_ref = self.layout.borrow_mut();
+        let layout = &mut *layout_ref;
+        let regions = (self.region_builder)(&self.decomposition, in_buffer.dims_2d(), &conf);
+        layout.update(regions, in_buffer.dims_2d(), generations, self.skip_inactive);
+        if layout.last_out_buffer != in_buffer.data.as_ptr() as usize {
+            layout.changed.iter_mut().for_each(|changed| *changed = true);
+        }
+        layout.last_out_buffer = out_buffer.as_ptr() as usize;
+        let active: Vec<bool> = match self.skip_inactive {
+            true => layout.dependencies.iter().map(|dependencies| dependencies.iter().any(|index| layout.changed[*index])).collect(),
+            false => vec![true; layout.regions.len()],
+        };
 
         let buffer = Arc::new(in_buffer);
         let conf = Arc::from(conf);
 
-        for i in 0..slice_count {
-            let buffer_clone = Arc::clone(&buffer);
-            let conf_clone = Arc::clone(&conf);
-            let count = self.work_slice_len;
-            let offset = i * self.work_slice_len;
-            let job = JobDescriptor {
-                buffer: buffer_clone,
-                conf: conf_clone,
-                out_buffer: self.get_slice(),
-                count,
-                offset,
-            };
-            self.job_queue.push(JobSignal::Work(job));
-        }
-        if slice_leftover > 0 {
+        let enqueue_start = Instant::now();
+        let enqueue_start_busy = self.main_thread_stats.busy();
+        let mut slices: Vec<JobResult<T>> = Vec::new();
+        let mut failure: Option<PanicPayload> = None;
+        let mut active_count = 0;
+        for (region_index, region) in layout.regions.iter().enumerate() {
+            if !active[region_index] {
+                continue;
+            }
             let buffer_clone = Arc::clone(&buffer);
             let conf_clone = Arc::clone(&conf);
-            let count = slice_leftover;
-            let offset = slice_count * self.work_slice_len;
             let job = JobDescriptor {
                 buffer: buffer_clone,
                 conf: conf_clone,
                 out_buffer: self.get_slice(),
-                count,
-                offset,
+                region: *region,
+                region_index,
+                generations,
+                track_changes: self.skip_inactive,
             };
-            self.job_queue.push(JobSignal::Work(job));
+            // Bounded queues can be full, results are collected meanwhile so the workers don't stall on theirs
+            let mut signal = JobSignal::Work(job);
+            while let Err(rejected) = self.job_queue.try_push(signal) {
+                signal = rejected;
+                match self.collect_result(&mut slices) {
+                    Ok(true) => (),
+                    Ok(false) => thread::yield_now(),
+                    Err(payload) => {
+                        failure = Some(payload);
+                        break;
+                    },
+                }
+            }
+            if failure.is_some() {
+                break;
+            }
+            active_count += 1;
         }
 
-        let true_slice_count = slice_count + (if slice_leftover > 0 { 1 } else { 0 });
-
-        let mut slices: Vec<JobResult<T>> = Vec::with_capacity(true_slice_count);
-        for _ in 0..true_slice_count {
-            loop {
-                if let Some(result) = self.res_queue.pop() {
-                    slices.push(result);
-                    break;
-                } else {
-                    thread::yield_now();
-                }
+        // Results are polled rather than waited for, so dead workers are noticed
+        let wait_start = Instant::now();
+        let wait_start_busy = self.main_thread_stats.busy();
+        while failure.is_none() && slices.len() < active_count {
+            match self.collect_result(&mut slices) {
+                Ok(true) => (),
+                Ok(false) => thread::yield_now(),
+                Err(payload) => failure = Some(payload),
             }
         }
+        if let Some(payload) = failure {
+            self.shut_down();
+            return Err(ExecutorError::from_panic(payload));
+        }
+        let copy_start = Instant::now();
+        let copy_start_busy = self.main_thread_stats.busy();
         for slice in slices {
-            out_buffer[(slice.offset)..(slice.offset + slice.count)].copy_from_slice(&slice.buffer);
+            let mut copied = 0;
+            for row in slice.region.rows() {
+                let row_len = row.len();
+                out_buffer[row].copy_from_slice(&slice.buffer[copied..(copied + row_len)]);
+                copied += row_len;
+            }
+            layout.changed[slice.region_index] = slice.changed;
             self.push_slice(slice.buffer);
         }
-        return match Arc::try_unwrap(buffer) {
-            Ok(buffer) => buffer,
-            Err(arc) => panic!("Threaded execution error: Arc references weren't all dropped, {} remaining!", Arc::strong_count(&arc)),
+        for (region_index, region) in layout.regions.iter().enumerate() {
+            if !active[region_index] {
+                for row in region.rows() {
+                    out_buffer[row.clone()].copy_from_slice(&buffer.data[row]);
+                }
+                layout.changed[region_index] = false;
+            }
+        }
+
+        let (skipped, total) = self.skip_stats.get();
+        self.skip_stats.set((skipped + layout.regions.len() - active_count, total + layout.regions.len()));
+
+        let unwrap_start = Instant::now();
+        let unwrapped = Arc::try_unwrap(buffer);
+        let unwrap_end = Instant::now();
+        let times = self.phase_times.get();
+        self.phase_times.set(PhaseTimes {
+            computes: times.computes + 1,
+            setup: times.setup + (enqueue_start - setup_start),
+            enqueue: times.enqueue + (wait_start - enqueue_start).saturating_sub(wait_start_busy - enqueue_start_busy),
+            wait: times.wait + (copy_start - wait_start).saturating_sub(copy_start_busy - wait_start_busy),
+            running_jobs: times.running_jobs + (copy_start_busy - enqueue_start_busy),
+            copy: times.copy + (unwrap_start - copy_start),
+            unwrap: times.unwrap + (unwrap_end - unwrap_start),
+        });
+        let first_generation = self.generation.get() + 1;
+        self.generation.set(self.generation.get() + generations);
+        if let Some(spans) = self.generation_spans.borrow_mut().as_mut() {
+            spans.push(GenerationSpan { first: first_generation, count: generations, start: setup_start, end: unwrap_end });
+        }
+
+        return match unwrapped {
+            Ok(buffer) => Ok((buffer, generations)),
+            Err(arc) => Err(ExecutorError::WorkerFailure(format!("Arc references weren't all dropped, {} remaining", Arc::strong_count(&arc)))),
         };
     }
 }
 
-impl<T, TConf> Drop for ExecutorParallel<T, TConf> {
-    fn drop(&mut self) {
-        for _ in 0..self.threads.len() {
+impl<T, TConf> ExecutorParallel<T, TConf> {
+    // Stops and joins every worker, dropping the jobs that didn't start. The executor can't compute anymore afterwards.
+    fn shut_down(&self) {
+        self.is_shut_down.set(true);
+        while self.job_queue.pop().is_some() { }
+        let mut threads = self.threads.borrow_mut();
+        for _ in 0..threads.len() {
             self.job_queue.push(JobSignal::Death);
         }
-        while let Some(handle) = self.threads.pop() {
-            handle.join().unwrap();
+        while let Some(handle) = threads.pop() {
+            // Results are drained meanwhile, so workers can't stay stuck on a full result queue
+            while !handle.is_finished() {
+                while self.res_queue.pop().is_some() { }
+                thread::yield_now();
+            }
+            if handle.join().is_err() {
+                eprintln!("A worker thread panicked while shutting down");
+            }
+        }
+        while self.res_queue.pop().is_some() { }
+    }
+}
+
+impl<T, TConf> Drop for ExecutorParallel<T, TConf> {
+    fn drop(&mut self) {
+        if !self.is_shut_down.get() {
+            self.shut_down();
         }
     }
 }
@@ -365,10 +1172,49 @@ impl<T, TConf, TJobber: Jobber<T, TConf>> Executor<T, TConf> for ExecutorSingleT
         T: Clone + Copy,
         TConf: Clone,
 {
-    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Buffer<T> {
-        for index in 0..(in_buffer.len()) {
-            out_buffer[index] = TJobber::process_job(&in_buffer, index, &conf);
-        }
-        return in_buffer;
+    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Result<Buffer<T>, ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
+        TJobber::process_span(&in_buffer, 0..(in_buffer.len()), &conf, out_buffer);
+        return Ok(in_buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_depend_on_adjacent_tiles() {
+        // 3 by 2 tiles of 4x4
+        let regions = WorkDecomposition::Tiles(4, 4).regions((12, 8));
+        let dependencies = region_dependencies(&regions, 12, 1);
+        assert_eq!(dependencies[0], vec![0, 1, 3, 4]);
+        assert_eq!(dependencies[1], vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(dependencies[5], vec![1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn distance_widens_dependencies() {
+        let regions = WorkDecomposition::Tiles(4, 4).regions((12, 8));
+        assert!(region_dependencies(&regions, 12, 0).iter().enumerate().all(|(index, dependencies)| *dependencies == vec![index]));
+        assert_eq!(region_dependencies(&regions, 12, 5)[0], vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn spans_depend_on_the_rows_they_touch() {
+        // Spans of 6 on rows of 4, the second covering the end of row 1 and all of row 2
+        let regions = WorkDecomposition::Slices(6).regions((4, 6));
+        let dependencies = region_dependencies(&regions, 4, 1);
+        assert_eq!(dependencies[0], vec![0, 1]);
+        assert_eq!(dependencies[1], vec![0, 1, 2]);
+        assert_eq!(dependencies[3], vec![2, 3]);
+    }
+
+    #[test]
+    fn clipped_tiles_keep_their_dependencies() {
+        // The last column and row of tiles are clipped to 2 cells
+        let regions = WorkDecomposition::Tiles(4, 4).regions((6, 6));
+        assert_eq!(regions[3].bounding_box(6), (4, 4, 2, 2));
+        assert_eq!(region_dependencies(&regions, 6, 1)[3], vec![0, 1, 2, 3]);
     }
 }
@@ -0,0 +1,142 @@
+use crate::parallelism::Buffer;
+use crate::jobbers::gol::GolCell;
+
+// A Game of Life pattern as a tightly sized buffer, loaded from RLE (.rle) or plaintext (.cells) files
+pub struct Pattern {
+    pub cells: Buffer<GolCell>,
+}
+
+impl Pattern {
+    pub fn load(file_name: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(file_name).map_err(|_| format!("Unable to find or read pattern file {}", file_name))?;
+        let alive_cells = match file_name.ends_with(".cells") {
+            true => parse_plaintext(&content),
+            false => parse_rle(&content)?,
+        };
+        let width = alive_cells.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+        let height = alive_cells.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+        let mut cells = Buffer::from_value_2d((width, height), GolCell::Dead);
+        for (x, y) in alive_cells {
+            cells.data[x + y * width] = GolCell::Alive;
+        }
+        Ok(Self { cells })
+    }
+
+    // Always written as plaintext, whatever the extension, so it can be loaded back as a .cells file
+    pub fn save(&self, file_name: &str) -> Result<(), String> {
+        let mut content = format!("!Name: {}\n", file_name);
+        for row in self.cells.data.chunks_exact(self.cells.dims.0) {
+            content.extend(row.iter().map(|cell| if cell.is_alive() { 'O' } else { '.' }));
+            content.push('\n');
+        }
+        std::fs::write(file_name, content).map_err(|_| format!("Unable to write pattern file {}", file_name))
+    }
+}
+
+// Run length encoded patterns: b is a dead cell, o an alive one, $ ends a row and ! the pattern,
+// each optionally preceded by a repeat count
+fn parse_rle(content: &str) -> Result<Vec<(usize, usize)>, String> {
+    let mut alive_cells = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut count: Option<usize> = None;
+    let lines = content
+        .lines()
+        .map(str::trim)
+        // Skips comments and the "x = m, y = n" header
+        .filter(|line| !line.starts_with('#') && !line.starts_with('x'));
+    'lines: for line in lines {
+        for character in line.chars() {
+            match character {
+                '0'..='9' => {
+                    let digit = character.to_digit(10).unwrap() as usize;
+                    count = Some(count.unwrap_or(0) * 10 + digit);
+                },
+                'b' | '.' => x += count.take().unwrap_or(1),
+                '$' => {
+                    y += count.take().unwrap_or(1);
+                    x = 0;
+                },
+                '!' => break 'lines,
+                character if character.is_whitespace() => (),
+                // Every other letter is an alive state in multi-state RLE
+                character if character.is_ascii_alphabetic() => {
+                    let run = count.take().unwrap_or(1);
+                    alive_cells.extend((x..(x + run)).map(|cell_x| (cell_x, y)));
+                    x += run;
+                },
+                _ => return Err(format!("Unexpected character '{}' in RLE pattern", character)),
+            }
+        }
+    }
+    Ok(alive_cells)
+}
+
+// Plaintext patterns: one row per line, O is alive, lines starting with ! are comments
+fn parse_plaintext(content: &str) -> Vec<(usize, usize)> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('!'))
+        .enumerate()
+        .flat_map(|(y, line)| line
+            .chars()
+            .enumerate()
+            .filter(|(_, character)| *character == 'O' || *character == '*')
+            .map(move |(x, _)| (x, y))
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_glider() {
+        let glider = "#N Glider\n#C A comment line\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+        assert_eq!(parse_rle(glider), Ok(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]));
+    }
+
+    #[test]
+    fn rle_counts_span_digits_lines_and_rows() {
+        let pattern = "x = 14, y = 4\n12b\n2o2$\no!";
+        assert_eq!(parse_rle(pattern), Ok(vec![(12, 0), (13, 0), (0, 2)]));
+    }
+
+    #[test]
+    fn rle_stops_at_the_end_marker() {
+        assert_eq!(parse_rle("o!\no"), Ok(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn rle_multi_state_letters_are_alive() {
+        assert_eq!(parse_rle("A.B!"), Ok(vec![(0, 0), (2, 0)]));
+    }
+
+    #[test]
+    fn rle_rejects_unexpected_characters() {
+        assert!(parse_rle("2o?o!").is_err());
+    }
+
+    #[test]
+    fn plaintext_skips_comments() {
+        let pattern = "!Name: Glider\n.O.\n..*\nOOO\n";
+        assert_eq!(parse_plaintext(pattern), vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn saved_patterns_load_back() {
+        let file_name = std::env::temp_dir().join(format!("gol_cpu_bench_pattern_{}.cells", std::process::id()));
+        let file_name = file_name.to_str().unwrap();
+        let mut cells = Buffer::from_value_2d((4, 3), GolCell::Dead);
+        for index in [1, 6, 8, 11] {
+            cells.data[index] = GolCell::Alive;
+        }
+        let pattern = Pattern { cells };
+        pattern.save(file_name).unwrap();
+        let loaded = Pattern::load(file_name);
+        std::fs::remove_file(file_name).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.cells.dims_2d(), (4, 3));
+        assert!(loaded.cells.data == pattern.cells.data);
+    }
+}
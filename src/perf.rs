@@ -0,0 +1,204 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HardwareEvent {
+    Instructions,
+    Cycles,
+    CacheMisses,
+    BranchMisses,
+}
+
+impl HardwareEvent {
+    const ALL: [HardwareEvent; 4] = [HardwareEvent::Instructions, HardwareEvent::Cycles, HardwareEvent::CacheMisses, HardwareEvent::BranchMisses];
+}
+
+// Counts of a run, None for the events the CPU or the kernel doesn't count
+#[derive(Default)]
+pub struct CounterReading {
+    pub instructions: Option<u64>,
+    pub cycles: Option<u64>,
+    pub cache_misses: Option<u64>,
+    pub branch_misses: Option<u64>,
+}
+
+impl CounterReading {
+    fn set(&mut self, event: HardwareEvent, value: u64) {
+        match event {
+            HardwareEvent::Instructions => self.instructions = Some(value),
+            HardwareEvent::Cycles => self.cycles = Some(value),
+            HardwareEvent::CacheMisses => self.cache_misses = Some(value),
+            HardwareEvent::BranchMisses => self.branch_misses = Some(value),
+        }
+    }
+
+    pub fn print(&self, cell_updates: usize) {
+        let per_update = |count: u64| count as f64 / usize::max(cell_updates, 1) as f64;
+        let describe = |name: &str, count: Option<u64>| match count {
+            Some(count) => println!("{}: {} ({:.3} per cell update)", name, count, per_update(count)),
+            None => println!("{}: unavailable", name),
+        };
+        describe("Instructions", self.instructions);
+        describe("Cycles", self.cycles);
+        if let (Some(instructions), Some(cycles)) = (self.instructions, self.cycles) {
+            println!("Instructions per cycle: {:.3}", instructions as f64 / u64::max(cycles, 1) as f64);
+        }
+        describe("Cache misses", self.cache_misses);
+        describe("Branch misses", self.branch_misses);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::convert::TryInto;
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use super::{CounterReading, HardwareEvent};
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+    const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+    const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+    // Bits of the attribute's flags bitfield
+    const ATTR_DISABLED: u64 = 1 << 0;
+    const ATTR_INHERIT: u64 = 1 << 1;
+    const ATTR_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const ATTR_EXCLUDE_HV: u64 = 1 << 6;
+    // _IO('$', n)
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+    const PERF_EVENT_PARANOID: &str = "/proc/sys/kernel/perf_event_paranoid";
+
+    // The first version of perf_event_attr, which every kernel accepts
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+    }
+
+    // One counter per event for the calling thread and the threads it spawns after opening them, in user space only
+    pub struct PerfCounters {
+        counters: Vec<(HardwareEvent, File)>,
+    }
+
+    impl PerfCounters {
+        // Opens the counters the CPU and the kernel allow, failing only if none could be
+        pub fn open() -> Result<Self, String> {
+            let mut counters = Vec::new();
+            let mut errors = Vec::new();
+            for event in HardwareEvent::ALL {
+                match open_counter(event) {
+                    Ok(file) => counters.push((event, file)),
+                    Err(error) => errors.push(error),
+                }
+            }
+            match (counters.is_empty(), errors.first()) {
+                (true, Some(error)) => Err(describe_open_error(error)),
+                _ => Ok(Self { counters }),
+            }
+        }
+
+        pub fn start(&self) {
+            for (_, file) in &self.counters {
+                // SAFETY: the descriptor is an open perf event, these ioctls take no argument
+                unsafe {
+                    libc::ioctl(file.as_raw_fd(), PERF_EVENT_IOC_RESET, 0);
+                    libc::ioctl(file.as_raw_fd(), PERF_EVENT_IOC_ENABLE, 0);
+                }
+            }
+        }
+
+        // Counts scaled up when the kernel had to share the hardware counters between events
+        pub fn stop(&mut self) -> CounterReading {
+            let mut reading = CounterReading::default();
+            for (event, file) in &mut self.counters {
+                // SAFETY: as in start()
+                unsafe {
+                    libc::ioctl(file.as_raw_fd(), PERF_EVENT_IOC_DISABLE, 0);
+                }
+                // Value, time enabled and time running
+                let mut values = [0_u8; 24];
+                if file.read_exact(&mut values).is_err() {
+                    continue;
+                }
+                let field = |index: usize| u64::from_ne_bytes(values[(index * 8)..(index * 8 + 8)].try_into().unwrap());
+                let (value, enabled, running) = (field(0), field(1), field(2));
+                if running > 0 {
+                    reading.set(*event, (value as f64 * enabled as f64 / running as f64) as u64);
+                }
+            }
+            reading
+        }
+    }
+
+    fn open_counter(event: HardwareEvent) -> io::Result<File> {
+        let attr = PerfEventAttr {
+            kind: PERF_TYPE_HARDWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config: match event {
+                HardwareEvent::Instructions => PERF_COUNT_HW_INSTRUCTIONS,
+                HardwareEvent::Cycles => PERF_COUNT_HW_CPU_CYCLES,
+                HardwareEvent::CacheMisses => PERF_COUNT_HW_CACHE_MISSES,
+                HardwareEvent::BranchMisses => PERF_COUNT_HW_BRANCH_MISSES,
+            },
+            read_format: PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING,
+            // Excluding the kernel is what unprivileged users may count at the default paranoia level
+            flags: ATTR_DISABLED | ATTR_INHERIT | ATTR_EXCLUDE_KERNEL | ATTR_EXCLUDE_HV,
+            ..PerfEventAttr::default()
+        };
+        // SAFETY: attr outlives the call, pid 0 and cpu -1 count the calling thread on any CPU, without a group
+        let fd = unsafe { libc::syscall(libc::SYS_perf_event_open, &attr as *const PerfEventAttr, 0, -1, -1, PERF_FLAG_FD_CLOEXEC) };
+        match fd {
+            -1 => Err(io::Error::last_os_error()),
+            // SAFETY: the descriptor was just opened and nothing else owns it
+            fd => Ok(unsafe { File::from_raw_fd(fd as i32) }),
+        }
+    }
+
+    fn describe_open_error(error: &io::Error) -> String {
+        match error.raw_os_error() {
+            Some(libc::EACCES) | Some(libc::EPERM) => {
+                let paranoid = std::fs::read_to_string(PERF_EVENT_PARANOID).map(|level| level.trim().to_string());
+                format!(
+                    "access to the hardware counters is denied (perf_event_paranoid is {}, counting needs it at most 2 or CAP_PERFMON)",
+                    paranoid.unwrap_or_else(|_| String::from("unknown")),
+                )
+            },
+            Some(libc::ENOENT) | Some(libc::EOPNOTSUPP) | Some(libc::ENODEV) => {
+                String::from("the CPU or the kernel doesn't expose hardware counters, e.g. in a virtual machine")
+            },
+            _ => format!("perf_event_open failed: {}", error),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::PerfCounters;
+
+#[cfg(not(target_os = "linux"))]
+pub struct PerfCounters { }
+
+#[cfg(not(target_os = "linux"))]
+impl PerfCounters {
+    pub fn open() -> Result<Self, String> {
+        Err(String::from("hardware counters are only supported on Linux"))
+    }
+
+    pub fn start(&self) { }
+
+    pub fn stop(&mut self) -> CounterReading {
+        CounterReading::default()
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use crossbeam_queue::{ArrayQueue, SegQueue};
+use serde::{Serialize, Deserialize};
+
+// Capacity of the bounded queues, pushing to a full one fails until items are popped
+const ARRAY_QUEUE_CAPACITY: usize = 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueBackend {
+    // Lock-free unbounded queue
+    SegQueue,
+    // Lock-free bounded queue
+    ArrayQueue,
+    // VecDeque behind a mutex, with a condition variable to sleep on while it's empty
+    MutexCondvar,
+    // Standard library channel, its receiver behind a mutex to be shared between workers
+    Channel,
+}
+
+// Multi-producer multi-consumer queue used to hand jobs to the workers and results back
+pub trait JobQueue<T>: Send + Sync {
+    // Gives the item back if the queue is full
+    fn try_push(&self, item: T) -> Result<(), T>;
+
+    fn pop(&self) -> Option<T>;
+
+    fn push(&self, mut item: T) {
+        loop {
+            match self.try_push(item) {
+                Ok(()) => return,
+                Err(rejected) => {
+                    item = rejected;
+                    thread::yield_now();
+                },
+            }
+        }
+    }
+
+    // Waits for an item, by yielding unless the backend can sleep until one is pushed
+    fn pop_wait(&self) -> T {
+        loop {
+            if let Some(item) = self.pop() {
+                return item;
+            }
+            thread::yield_now();
+        }
+    }
+}
+
+pub fn new_queue<T: 'static + Send>(backend: QueueBackend) -> Arc<dyn JobQueue<T>> {
+    match backend {
+        QueueBackend::SegQueue => Arc::new(SegQueue::new()),
+        QueueBackend::ArrayQueue => Arc::new(ArrayQueue::new(ARRAY_QUEUE_CAPACITY)),
+        QueueBackend::MutexCondvar => Arc::new(MutexQueue::new()),
+        QueueBackend::Channel => Arc::new(ChannelQueue::new()),
+    }
+}
+
+impl<T: Send> JobQueue<T> for SegQueue<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        SegQueue::push(self, item);
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        SegQueue::pop(self)
+    }
+}
+
+impl<T: Send> JobQueue<T> for ArrayQueue<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        ArrayQueue::push(self, item)
+    }
+
+    fn pop(&self) -> Option<T> {
+        ArrayQueue::pop(self)
+    }
+}
+
+pub struct MutexQueue<T> {
+    items: Mutex<VecDeque<T>>,
+    available: Condvar,
+}
+
+impl<T> MutexQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            items: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+        }
+    }
+}
+
+impl<T: Send> JobQueue<T> for MutexQueue<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.items.lock().unwrap().push_back(item);
+        self.available.notify_one();
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.items.lock().unwrap().pop_front()
+    }
+
+    fn pop_wait(&self) -> T {
+        let mut items = self.available.wait_while(self.items.lock().unwrap(), |items| items.is_empty()).unwrap();
+        items.pop_front().unwrap()
+    }
+}
+
+pub struct ChannelQueue<T> {
+    sender: Sender<T>,
+    receiver: Mutex<Receiver<T>>,
+}
+
+impl<T> ChannelQueue<T> {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl<T: Send> JobQueue<T> for ChannelQueue<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        // Sending only fails once the receiver is dropped, which the queue owns
+        self.sender.send(item).map_err(|error| error.0)
+    }
+
+    // A worker waiting for an item holds the receiver, the queue then counts as empty rather than blocking
+    fn pop(&self) -> Option<T> {
+        self.receiver.try_lock().ok()?.try_recv().ok()
+    }
+
+    fn pop_wait(&self) -> T {
+        self.receiver.lock().unwrap().recv().unwrap()
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use crate::parallelism::{Buffer, Executor, ExecutorError, RunLength};
+use crate::interrupt::is_interrupted;
+
+const STRESS_STATUS_INTERVAL: Duration = Duration::from_secs(10);
+// Positions listed when reporting a mismatch, the rest are only counted
+const MAX_REPORTED_CELLS: usize = 16;
+// Block length for executors pipelining the whole run in one compute, enough for the generations to overlap
+const PIPELINED_CHECKED_GENERATIONS: usize = 2;
+
+pub struct StressOutcome<T> {
+    pub buffer: Buffer<T>,
+    pub generations: usize,
+    pub verified: usize,
+    pub mismatch: Option<Mismatch>,
+}
+
+// The last generation of a verified block the executor and the reference disagree on, given the same input buffer
+pub struct Mismatch {
+    pub generation: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "generation {} differs from the reference at {} cell(s): ", self.generation, self.cells.len())?;
+        let listed: Vec<String> = self.cells.iter().take(MAX_REPORTED_CELLS).map(|(x, y)| format!("({}, {})", x, y)).collect();
+        write!(f, "{}", listed.join(", "))?;
+        if self.cells.len() > MAX_REPORTED_CELLS {
+            write!(f, " and {} more", self.cells.len() - MAX_REPORTED_CELLS)?;
+        }
+        Ok(())
+    }
+}
+
+// Runs the executor for the duration, verifying a block of generations out of every check_interval until the first mismatch:
+// the reference recomputes the block from a snapshot of its input, and both results are compared. The executor computes
+// the block the way it computes the rest of the run, so temporal blocking and pipelined scheduling get verified too.
+// Generations in between run at full speed, so the load stays close to a benchmark's.
+pub fn stress<T, TConf>(
+    executor: &dyn Executor<T, TConf>,
+    reference: &dyn Executor<T, TConf>,
+    duration: Duration,
+    check_interval: usize,
+    mut buffer: Buffer<T>,
+    conf: TConf,
+) -> Result<StressOutcome<T>, ExecutorError>
+    where
+        T: Copy + PartialEq,
+        TConf: Clone,
+{
+    let check_interval = usize::max(check_interval, 1);
+    let checked_generations = match executor.max_generations_per_compute() {
+        usize::MAX => PIPELINED_CHECKED_GENERATIONS,
+        max_generations => usize::max(max_generations, 2),
+    };
+    let checked_generations = usize::min(checked_generations, check_interval);
+    let unchecked_generations = check_interval - checked_generations;
+    let start = Instant::now();
+    let mut last_status = start;
+    let mut generations = 0;
+    let mut verified = 0;
+    while start.elapsed() < duration && !is_interrupted() {
+        let completed;
+        (buffer, completed) = executor.advance(RunLength::Iterations(unchecked_generations), buffer, TConf::clone(&conf))?;
+        generations += completed;
+        if completed < unchecked_generations {
+            break;
+        }
+
+        let snapshot = Buffer::clone(&buffer);
+        let (result, completed) = executor.advance(RunLength::Iterations(checked_generations), buffer, TConf::clone(&conf))?;
+        // An interrupted block is still compared, over the generations the executor completed
+        let (expected, _) = reference.advance(RunLength::Iterations(completed), snapshot, TConf::clone(&conf))?;
+        generations += completed;
+        verified += completed;
+        let cells: Vec<(usize, usize)> = result.data
+            .iter()
+            .zip(expected.data.iter())
+            .enumerate()
+            .filter(|(_, (cell, expected_cell))| cell != expected_cell)
+            .map(|(index, _)| result.index_to_pos_2d(index))
+            .collect();
+        if !cells.is_empty() {
+            return Ok(StressOutcome {
+                buffer: expected,
+                generations,
+                verified,
+                mismatch: Some(Mismatch { generation: generations, cells }),
+            });
+        }
+        buffer = result;
+        if completed < checked_generations {
+            break;
+        }
+
+        if last_status.elapsed() >= STRESS_STATUS_INTERVAL {
+            println!("{:>6.1}s: {} generations, {} verified", start.elapsed().as_secs_f32(), generations, verified);
+            last_status = Instant::now();
+        }
+    }
+    Ok(StressOutcome { buffer, generations, verified, mismatch: None })
+}
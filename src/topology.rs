@@ -0,0 +1,311 @@
+use std::marker::PhantomData;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+const SYSFS_CPU_ROOT: &str = "/sys/devices/system/cpu";
+// Intel hybrid parts expose their performance cores as a separate PMU device
+const SYSFS_INTEL_CORE_CPUS: &str = "/sys/devices/cpu_core/cpus";
+
+// Which logical CPUs the benchmark should spawn worker threads for
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadSelection {
+    // Every logical CPU, so one thread per SMT sibling
+    Logical,
+    // A single logical CPU per physical core
+    Physical,
+    // Every logical CPU belonging to the highest capacity cores
+    Performance,
+}
+
+#[derive(Clone, Debug)]
+pub struct CpuInfo {
+    pub id: usize,
+    pub core_id: usize,
+    pub package_id: usize,
+    pub thread_siblings: Vec<usize>,
+    // Relative compute capacity, higher is faster; None if the platform doesn't report one
+    pub capacity: Option<usize>,
+}
+
+// A cache level as seen by the first CPU
+#[derive(Clone, Debug)]
+pub struct CacheInfo {
+    pub level: usize,
+    // Data, Instruction or Unified
+    pub kind: String,
+    pub size: usize,
+    pub shared_cpus: Vec<usize>,
+}
+
+impl CacheInfo {
+    // Whether the cache holds data, which is all the benchmark's buffers care about
+    pub fn holds_data(&self) -> bool {
+        self.kind != "Instruction"
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CpuTopology {
+    pub cpus: Vec<CpuInfo>,
+}
+
+// Parses the kernel's cpulist format, e.g. 0-3,8,10-11
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => {
+                let start = start.parse::<usize>().ok()?;
+                let end = end.parse::<usize>().ok()?;
+                cpus.extend(start..=end);
+            },
+            None => cpus.push(range.parse::<usize>().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+fn read_trimmed<P: AsRef<Path>>(path: P) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|content| content.trim().to_string())
+}
+
+fn read_usize<P: AsRef<Path>>(path: P) -> Option<usize> {
+    read_trimmed(path)?.parse::<usize>().ok()
+}
+
+// Parses sysfs cache sizes, e.g. 48K or 2M, into bytes
+pub fn parse_cache_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let (digits, multiplier) = match size.chars().last()? {
+        'K' => (&size[..(size.len() - 1)], 1 << 10),
+        'M' => (&size[..(size.len() - 1)], 1 << 20),
+        'G' => (&size[..(size.len() - 1)], 1 << 30),
+        _ => (size, 1),
+    };
+    Some(digits.parse::<usize>().ok()? * multiplier)
+}
+
+// Reads the caches of the first CPU from sysfs, sorted by level, empty if they aren't available
+pub fn detect_caches() -> Vec<CacheInfo> {
+    let cache_dir = Path::new(SYSFS_CPU_ROOT).join("cpu0/cache");
+    let mut caches: Vec<CacheInfo> = std::fs::read_dir(&cache_dir)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("index"))
+            .filter_map(|entry| {
+                let index_dir = entry.path();
+                Some(CacheInfo {
+                    level: read_usize(index_dir.join("level"))?,
+                    kind: read_trimmed(index_dir.join("type"))?,
+                    size: parse_cache_size(&read_trimmed(index_dir.join("size"))?)?,
+                    shared_cpus: read_trimmed(index_dir.join("shared_cpu_list"))
+                        .and_then(|list| parse_cpu_list(&list))
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+        )
+        .unwrap_or_default();
+    caches.sort_by_key(|cache| (cache.level, cache.kind.clone()));
+    caches
+}
+
+impl CpuTopology {
+    // Reads the topology of every online CPU from sysfs, None if it isn't available (e.g. not on Linux)
+    pub fn detect() -> Option<Self> {
+        let root = Path::new(SYSFS_CPU_ROOT);
+        let mut online = parse_cpu_list(&read_trimmed(root.join("online"))?)?;
+        // CPUs outside the affinity mask (taskset, cpusets) can't run the benchmark's threads
+        let allowed = affinity_cpus();
+        if let Some(allowed) = &allowed {
+            online.retain(|id| allowed.contains(id));
+        }
+        // On hybrid Intel CPUs cpu_capacity is absent, but the P-core list is exposed
+        let intel_core_cpus = read_trimmed(SYSFS_INTEL_CORE_CPUS).and_then(|list| parse_cpu_list(&list));
+
+        let mut cpus = Vec::with_capacity(online.len());
+        for id in online {
+            let cpu_dir = root.join(format!("cpu{}", id));
+            let topology_dir = cpu_dir.join("topology");
+            // Max frequencies aren't used, they also differ between the favored cores of non-hybrid parts
+            let capacity = read_usize(cpu_dir.join("cpu_capacity"))
+                .or_else(|| intel_core_cpus.as_ref().map(|core_cpus| if core_cpus.contains(&id) { 1 } else { 0 }));
+            cpus.push(CpuInfo {
+                id,
+                core_id: read_usize(topology_dir.join("core_id")).unwrap_or(id),
+                package_id: read_usize(topology_dir.join("physical_package_id")).unwrap_or(0),
+                thread_siblings: read_trimmed(topology_dir.join("thread_siblings_list"))
+                    .and_then(|list| parse_cpu_list(&list))
+                    .map(|siblings| siblings.into_iter().filter(|sibling| allowed.as_ref().is_none_or(|allowed| allowed.contains(sibling))).collect())
+                    .unwrap_or_else(|| vec![id]),
+                capacity,
+            });
+        }
+        if cpus.is_empty() { None } else { Some(Self { cpus }) }
+    }
+
+    pub fn logical_cpus(&self) -> Vec<usize> {
+        self.cpus.iter().map(|cpu| cpu.id).collect()
+    }
+
+    // The first logical CPU of each physical core
+    pub fn physical_cores(&self) -> Vec<usize> {
+        self.cpus
+            .iter()
+            .filter(|cpu| cpu.thread_siblings.iter().min().is_none_or(|first| *first == cpu.id))
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    pub fn package_count(&self) -> usize {
+        let mut packages: Vec<usize> = self.cpus.iter().map(|cpu| cpu.package_id).collect();
+        packages.sort_unstable();
+        packages.dedup();
+        packages.len()
+    }
+
+    // Logical CPUs of the cores with the highest reported capacity
+    // All CPUs are returned when capacities are not reported or identical
+    pub fn performance_cpus(&self) -> Vec<usize> {
+        let max_capacity = self.cpus.iter().filter_map(|cpu| cpu.capacity).max();
+        self.cpus
+            .iter()
+            .filter(|cpu| max_capacity.is_none() || cpu.capacity == max_capacity)
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    pub fn is_hybrid(&self) -> bool {
+        self.performance_cpus().len() != self.cpus.len()
+    }
+
+    pub fn select(&self, selection: ThreadSelection) -> Vec<usize> {
+        match selection {
+            ThreadSelection::Logical => self.logical_cpus(),
+            ThreadSelection::Physical => self.physical_cores(),
+            ThreadSelection::Performance => self.performance_cpus(),
+        }
+    }
+}
+
+impl std::fmt::Display for CpuTopology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} logical CPU(s), {} physical core(s), {} package(s), {} performance CPU(s){}",
+            self.cpus.len(),
+            self.physical_cores().len(),
+            self.package_count(),
+            self.performance_cpus().len(),
+            if self.is_hybrid() { " (hybrid)" } else { "" },
+        )?;
+        for cpu in &self.cpus {
+            writeln!(
+                f,
+                "    cpu{}: package {}, core {}, siblings {:?}, capacity {}",
+                cpu.id,
+                cpu.package_id,
+                cpu.core_id,
+                cpu.thread_siblings,
+                cpu.capacity.map_or(String::from("unknown"), |capacity| capacity.to_string()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Logical CPU count for a selection, falling back to num_cpus when the topology can't be read
+pub fn selection_thread_count(topology: Option<&CpuTopology>, selection: ThreadSelection) -> usize {
+    // num_cpus accounts for the affinity mask and cgroup CPU quotas, which sysfs doesn't
+    let allowed = num_cpus::get();
+    match (topology, selection) {
+        (_, ThreadSelection::Logical) => allowed,
+        (Some(topology), _) => usize::min(topology.select(selection).len(), allowed),
+        (None, ThreadSelection::Physical) => usize::min(num_cpus::get_physical(), allowed),
+        (None, ThreadSelection::Performance) => allowed,
+    }
+}
+
+// Logical CPUs the process may run on, None if they can't be read
+#[cfg(target_os = "linux")]
+pub fn affinity_cpus() -> Option<Vec<usize>> {
+    // SAFETY: the zeroed set is a valid cpu_set_t for sched_getaffinity to fill, its size is passed along so the
+    // kernel never writes past it, and pid 0 reads the calling thread's own mask
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return None;
+        }
+        Some((0..libc::CPU_SETSIZE as usize).filter(|cpu| libc::CPU_ISSET(*cpu, &set)).collect())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn affinity_cpus() -> Option<Vec<usize>> {
+    None
+}
+
+// Pins the calling thread to a single logical CPU, returns whether it succeeded
+pub fn pin_current_thread(cpu: usize) -> bool {
+    set_current_thread_affinity(&[cpu])
+}
+
+// Wraps a worker thread's body so the thread first pins itself to the CPU at cpu_index in cpus, wrapping around,
+// and warns if it can't. Threads running it are left unpinned when there are no CPUs to pin to.
+pub fn pinned_worker<F, R>(cpus: &[usize], cpu_index: usize, body: F) -> impl FnOnce() -> R
+    where F: FnOnce() -> R
+{
+    let cpu = if cpus.is_empty() { None } else { Some(cpus[cpu_index % cpus.len()]) };
+    move || {
+        if let Some(cpu) = cpu {
+            if !pin_current_thread(cpu) {
+                eprintln!("Unable to pin worker thread to cpu{}", cpu);
+            }
+        }
+        body()
+    }
+}
+
+// Restricts the calling thread to the given logical CPUs, returns whether it succeeded
+#[cfg(target_os = "linux")]
+fn set_current_thread_affinity(cpus: &[usize]) -> bool {
+    // SAFETY: the set starts zeroed, a valid empty cpu_set_t, and CPU_SET is only called with CPUs below CPU_SETSIZE
+    // so it stays within it. sched_setaffinity only reads the set, and pid 0 changes the calling thread's mask alone.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus.iter().filter(|cpu| **cpu < libc::CPU_SETSIZE as usize) {
+            libc::CPU_SET(*cpu, &mut set);
+        }
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_current_thread_affinity(_cpus: &[usize]) -> bool {
+    false
+}
+
+// Keeps the calling thread pinned until dropped, which lets it run on the CPUs it could before again.
+// Affinity is per thread, so it can't be sent to another one.
+pub struct ScopedPin {
+    previous: Vec<usize>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ScopedPin {
+    // None if the current affinity can't be read or the thread can't be pinned
+    pub fn pin_current_thread(cpu: usize) -> Option<Self> {
+        let previous = affinity_cpus()?;
+        match pin_current_thread(cpu) {
+            true => Some(Self { previous, _not_send: PhantomData }),
+            false => None,
+        }
+    }
+}
+
+impl Drop for ScopedPin {
+    fn drop(&mut self) {
+        set_current_thread_affinity(&self.previous);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt::Write as _;
+use std::io;
+use std::time::Instant;
+
+// A job run by one of an executor's threads, thread 0 being the dispatching thread and worker i thread i + 1
+pub struct JobSpan {
+    pub thread: usize,
+    pub offset: usize,
+    pub count: usize,
+    pub generations: usize,
+    pub start: Instant,
+    pub end: Instant,
+}
+
+// Generations first..first + count computed between two synchronizations of the threads
+pub struct GenerationSpan {
+    pub first: usize,
+    pub count: usize,
+    pub start: Instant,
+    pub end: Instant,
+}
+
+#[derive(Default)]
+pub struct Trace {
+    pub thread_names: Vec<String>,
+    pub jobs: Vec<JobSpan>,
+    pub generations: Vec<GenerationSpan>,
+}
+
+impl Trace {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty() && self.generations.is_empty()
+    }
+
+    fn origin(&self) -> Option<Instant> {
+        let job_starts = self.jobs.iter().map(|job| job.start);
+        let generation_starts = self.generations.iter().map(|generation| generation.start);
+        job_starts.chain(generation_starts).min()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            character if (character as u32) < 0x20 => write!(escaped, "\\u{:04x}", character as u32).unwrap(),
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Microseconds since the origin, the trace-event format's time unit
+fn micros(origin: Instant, instant: Instant) -> f64 {
+    instant.saturating_duration_since(origin).as_nanos() as f64 / 1000_f64
+}
+
+// Writes the traces in the Chrome trace-event format, which Perfetto and chrome://tracing open.
+// Each trace is a process named after its label, with one track per thread, and starts at 0.
+pub fn write_chrome_trace(file_name: &str, traces: &[(String, Trace)]) -> io::Result<()> {
+    let mut events: Vec<String> = Vec::new();
+    for (pid, (label, trace)) in traces.iter().enumerate() {
+        events.push(format!(r#"{{"name":"process_name","ph":"M","pid":{},"tid":0,"args":{{"name":{}}}}}"#, pid, json_string(label)));
+        for (tid, name) in trace.thread_names.iter().enumerate() {
+            events.push(format!(r#"{{"name":"thread_name","ph":"M","pid":{},"tid":{},"args":{{"name":{}}}}}"#, pid, tid, json_string(name)));
+        }
+        let origin = match trace.origin() {
+            Some(origin) => origin,
+            None => continue,
+        };
+        for generation in &trace.generations {
+            let name = match generation.count {
+                1 => format!("Generation {}", generation.first),
+                count => format!("Generations {}-{}", generation.first, generation.first + count - 1),
+            };
+            events.push(format!(
+                r#"{{"name":{},"cat":"generation","ph":"X","pid":{},"tid":0,"ts":{:.3},"dur":{:.3},"args":{{"first":{},"count":{}}}}}"#,
+                json_string(&name),
+                pid,
+                micros(origin, generation.start),
+                micros(generation.start, generation.end),
+                generation.first,
+                generation.count,
+            ));
+        }
+        for job in &trace.jobs {
+            events.push(format!(
+                r#"{{"name":"Job","cat":"job","ph":"X","pid":{},"tid":{},"ts":{:.3},"dur":{:.3},"args":{{"offset":{},"count":{},"generations":{}}}}}"#,
+                pid,
+                job.thread,
+                micros(origin, job.start),
+                micros(job.start, job.end),
+                job.offset,
+                job.count,
+                job.generations,
+            ));
+        }
+    }
+    let json = format!("{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"));
+    std::fs::write(file_name, json)
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Barrier, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+use crate::parallelism::{Jobber, Buffer, ExecutorError, PanicPayload, RunLength};
+use crate::jobbers::gol::GolCell;
+use crate::interrupt::is_interrupted;
+
+type ChunkPos = (i64, i64);
+
+const NEIGHBOR_CHUNKS: [ChunkPos; 8] = [
+    (-1, -1),
+    ( 0, -1),
+    ( 1, -1),
+    (-1,  0),
+    ( 1,  0),
+    (-1,  1),
+    ( 0,  1),
+    ( 1,  1),
+];
+
+// An unbounded universe made of square chunks, which are allocated as live cells approach their edges
+// and freed once they are empty. Cell (0, 0) is the top left cell of chunk (0, 0).
+pub struct ChunkedUniverse {
+    chunk_size: usize,
+    chunks: HashMap<ChunkPos, Buffer<GolCell>>,
+    generation: u64,
+}
+
+impl ChunkedUniverse {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: usize::max(chunk_size, 1),
+            chunks: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    // Only the buffer's live cells are set, so chunks are only allocated where it has some, cell (0, 0) mapping to chunk (0, 0)
+    pub fn from_buffer(buffer: &Buffer<GolCell>, chunk_size: usize) -> Self {
+        let mut universe = Self::new(chunk_size);
+        for (index, cell) in buffer.data.iter().enumerate() {
+            if cell.is_alive() {
+                let (x, y) = buffer.index_to_pos_2d(index);
+                universe.set((x as i64, y as i64), GolCell::Alive);
+            }
+        }
+        universe
+    }
+
+    fn split_pos(&self, pos: (i64, i64)) -> (ChunkPos, usize) {
+        let size = self.chunk_size as i64;
+        let chunk_pos = (pos.0.div_euclid(size), pos.1.div_euclid(size));
+        let (x, y) = (pos.0.rem_euclid(size) as usize, pos.1.rem_euclid(size) as usize);
+        (chunk_pos, x + y * self.chunk_size)
+    }
+
+    pub fn set(&mut self, pos: (i64, i64), cell: GolCell) {
+        let (chunk_pos, index) = self.split_pos(pos);
+        let chunk_size = self.chunk_size;
+        self.chunks
+            .entry(chunk_pos)
+            .or_insert_with(|| Buffer::from_value_2d((chunk_size, chunk_size), GolCell::Dead))
+            .data[index] = cell;
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn population(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.data.iter().filter(|cell| cell.is_alive()).count()).sum()
+    }
+
+    // Allocates the neighbor chunks that live cells on a chunk's edges can spread into
+    fn grow(&mut self) {
+        let size = self.chunk_size;
+        let mut missing = Vec::new();
+        for (chunk_pos, chunk) in self.chunks.iter() {
+            let row_alive = |y: usize| chunk.data[(y * size)..((y + 1) * size)].iter().any(GolCell::is_alive);
+            let column_alive = |x: usize| (0..size).any(|y| chunk.data[x + y * size].is_alive());
+            let (left, right, top, bottom) = (column_alive(0), column_alive(size - 1), row_alive(0), row_alive(size - 1));
+            for (offset_x, offset_y) in NEIGHBOR_CHUNKS {
+                let horizontal = match offset_x { -1 => left, 1 => right, _ => true };
+                let vertical = match offset_y { -1 => top, 1 => bottom, _ => true };
+                let neighbor_pos = (chunk_pos.0 + offset_x, chunk_pos.1 + offset_y);
+                if horizontal && vertical && !self.chunks.contains_key(&neighbor_pos) {
+                    missing.push(neighbor_pos);
+                }
+            }
+        }
+        for chunk_pos in missing {
+            self.chunks.entry(chunk_pos).or_insert_with(|| Buffer::from_value_2d((size, size), GolCell::Dead));
+        }
+    }
+
+    // Frees the empty chunks, keeping their buffers in spare
+    fn shrink(&mut self, spare: &mut Vec<Buffer<GolCell>>) {
+        let empty: Vec<ChunkPos> = self.chunks
+            .iter()
+            .filter(|(_, chunk)| !chunk.data.iter().any(GolCell::is_alive))
+            .map(|(chunk_pos, _)| *chunk_pos)
+            .collect();
+        spare.extend(empty.iter().filter_map(|chunk_pos| self.chunks.remove(chunk_pos)));
+    }
+
+    // The chunk surrounded by a one cell border taken from its neighbors, dead where there are none,
+    // written to a (chunk_size + 2) square buffer reused from chunk to chunk
+    fn pad_chunk(&self, chunk_pos: ChunkPos, padded: &mut Buffer<GolCell>) {
+        let size = self.chunk_size;
+        let padded_size = self.chunk_size + 2;
+        padded.data.fill(GolCell::Dead);
+        let chunk = &self.chunks[&chunk_pos];
+        for y in 0..self.chunk_size {
+            let padded_offset = 1 + (y + 1) * padded_size;
+            padded.data[padded_offset..(padded_offset + self.chunk_size)]
+                .copy_from_slice(&chunk.data[(y * self.chunk_size)..((y + 1) * self.chunk_size)]);
+        }
+        // Looked up once per neighbor rather than once per border cell
+        for (offset_x, offset_y) in NEIGHBOR_CHUNKS {
+            let neighbor = match self.chunks.get(&(chunk_pos.0 + offset_x, chunk_pos.1 + offset_y)) {
+                Some(neighbor) => neighbor,
+                None => continue,
+            };
+            // Ranges of the neighbor's cells adjacent to the chunk, and where they land in the padded chunk
+            let span = |offset: i64| match offset {
+                -1 => (size - 1)..size,
+                0 => 0..size,
+                _ => 0..1,
+            };
+            let (xs, ys) = (span(offset_x), span(offset_y));
+            let padded_x = |x: usize| (x as i64 + 1 + offset_x * size as i64) as usize;
+            let padded_y = |y: usize| (y as i64 + 1 + offset_y * size as i64) as usize;
+            for y in ys {
+                for x in xs.clone() {
+                    padded.data[padded_x(x) + padded_y(y) * padded_size] = neighbor.data[x + y * self.chunk_size];
+                }
+            }
+        }
+    }
+}
+
+// State the workers of a run share, the universe only being written by the dispatching thread in between generations
+struct UniverseRun<'a> {
+    universe: RwLock<&'a mut ChunkedUniverse>,
+    chunk_positions: RwLock<Vec<ChunkPos>>,
+    next_chunk: AtomicUsize,
+    stepped: Mutex<Vec<(ChunkPos, Buffer<GolCell>)>>,
+    // Buffers of the chunks replaced or freed by previous generations, reused for the next ones
+    spare: Mutex<Vec<Buffer<GolCell>>>,
+    // What the first panicking worker panicked with
+    failure: Mutex<Option<PanicPayload>>,
+    is_finished: AtomicBool,
+    // Every worker waits on both barriers once per generation, the dispatching thread too
+    start: Barrier,
+    end: Barrier,
+}
+
+// Steps a ChunkedUniverse with one job per chunk, spread over threads that live as long as the run
+pub struct ExecutorUniverse<TJobber: Jobber<GolCell, ()>> {
+    thread_count: usize,
+    _phantom: PhantomData<TJobber>,
+}
+
+impl<TJobber: Jobber<GolCell, ()>> ExecutorUniverse<TJobber> {
+    pub fn new(thread_count: usize) -> Self {
+        Self {
+            thread_count: usize::max(thread_count, 1),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn step_chunk(universe: &ChunkedUniverse, chunk_pos: ChunkPos, padded: &mut Buffer<GolCell>, next: &mut Buffer<GolCell>) {
+        let size = universe.chunk_size;
+        universe.pad_chunk(chunk_pos, padded);
+        for (y, out_row) in next.data.chunks_exact_mut(size).enumerate() {
+            let row_offset = 1 + (y + 1) * (size + 2);
+            TJobber::process_span(padded, row_offset..(row_offset + size), &(), out_row);
+        }
+    }
+
+    // Steps the chunks handed out for each generation until the run is finished, keeping its padded chunk across them
+    fn work_loop(run: &UniverseRun) {
+        let size = run.universe.read().unwrap().chunk_size;
+        let mut padded = Buffer::from_value_2d((size + 2, size + 2), GolCell::Dead);
+        let mut stepped = Vec::new();
+        loop {
+            run.start.wait();
+            if run.is_finished.load(Ordering::SeqCst) {
+                return;
+            }
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                let universe = run.universe.read().unwrap();
+                let chunk_positions = run.chunk_positions.read().unwrap();
+                while let Some(chunk_pos) = chunk_positions.get(run.next_chunk.fetch_add(1, Ordering::Relaxed)) {
+                    let spare = run.spare.lock().unwrap().pop();
+                    let mut next = spare.unwrap_or_else(|| Buffer::from_value_2d((size, size), GolCell::Dead));
+                    Self::step_chunk(&universe, *chunk_pos, &mut padded, &mut next);
+                    stepped.push((*chunk_pos, next));
+                }
+            }));
+            match outcome {
+                Ok(()) => run.stepped.lock().unwrap().append(&mut stepped),
+                Err(payload) => {
+                    run.failure.lock().unwrap().get_or_insert(payload);
+                },
+            }
+            // Reached even after a panic, so the other threads aren't left waiting
+            run.end.wait();
+        }
+    }
+
+    // One generation, the workers stepping the chunks between the two barriers
+    fn step(run: &UniverseRun) -> Result<(), ExecutorError> {
+        {
+            let mut universe = run.universe.write().unwrap();
+            universe.grow();
+            *run.chunk_positions.write().unwrap() = universe.chunks.keys().copied().collect();
+        }
+        run.next_chunk.store(0, Ordering::Relaxed);
+        run.start.wait();
+        run.end.wait();
+        if let Some(payload) = run.failure.lock().unwrap().take() {
+            return Err(ExecutorError::from_panic(payload));
+        }
+        let mut universe = run.universe.write().unwrap();
+        let mut spare = run.spare.lock().unwrap();
+        for (chunk_pos, next) in run.stepped.lock().unwrap().drain(..) {
+            spare.extend(universe.chunks.insert(chunk_pos, next));
+        }
+        let chunk_count = universe.chunks.len();
+        universe.shrink(&mut spare);
+        // Freed chunks are only kept up to the live ones' count, so a pattern dying out returns its memory
+        spare.truncate(chunk_count);
+        universe.generation += 1;
+        Ok(())
+    }
+
+    // Steps a fixed count of generations, or as many as fit in the duration, on workers spawned once for the whole run
+    fn run(&self, iterations: usize, deadline: Option<Instant>, universe: &mut ChunkedUniverse) -> Result<usize, ExecutorError> {
+        let run = UniverseRun {
+            universe: RwLock::new(universe),
+            chunk_positions: RwLock::new(Vec::new()),
+            next_chunk: AtomicUsize::new(0),
+            stepped: Mutex::new(Vec::new()),
+            spare: Mutex::new(Vec::new()),
+            failure: Mutex::new(None),
+            is_finished: AtomicBool::new(false),
+            start: Barrier::new(self.thread_count + 1),
+            end: Barrier::new(self.thread_count + 1),
+        };
+        thread::scope(|scope| {
+            for _ in 0..self.thread_count {
+                scope.spawn(|| Self::work_loop(&run));
+            }
+            let mut completed = 0;
+            let mut outcome = Ok(());
+            while outcome.is_ok() && completed < iterations && !is_interrupted() && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+                outcome = Self::step(&run);
+                completed += 1;
+            }
+            run.is_finished.store(true, Ordering::SeqCst);
+            run.start.wait();
+            outcome.map(|()| completed)
+        })
+    }
+
+    // A duration is checked in between generations, like the dense executors do
+    pub fn compute_run(&self, length: RunLength, universe: &mut ChunkedUniverse) -> Result<(), ExecutorError> {
+        let now = Instant::now();
+        let (iterations, deadline) = match length {
+            RunLength::Iterations(iterations) => (iterations, None),
+            RunLength::Duration(duration) => (usize::MAX, Some(now + duration)),
+        };
+        let completed = self.run(iterations, deadline, universe)?;
+        if is_interrupted() {
+            println!("Interrupted after {} generations", completed);
+        } else if let RunLength::Duration(duration) = length {
+            println!("Completed {} generations in the {:?} budget", completed, duration);
+        }
+        let elapsed = now.elapsed().as_secs_f32();
+        println!(
+            "Time elapsed: {}s, {} generations per second, generation {}, population {}, {} chunks of {}x{}",
+            elapsed,
+            completed as f32 / f32::max(elapsed, f32::EPSILON),
+            universe.generation(),
+            universe.population(),
+            universe.chunk_count(),
+            universe.chunk_size,
+            universe.chunk_size,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+use crossbeam_queue::SegQueue;
+use serde::{Serialize, Deserialize};
+use crate::parallelism::{Buffer, Executor, ExecutorError, Jobber, JobRegion, PanicPayload, WorkDecomposition, check_buffers, region_dependencies};
+use crate::topology::{ScopedPin, pinned_worker};
+use crate::interrupt::is_interrupted;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheduling {
+    // Every region finishes a generation before any starts the next one
+    Barrier,
+    // Each region starts its next generation as soon as it and its neighbors finished the current one
+    Wavefront,
+}
+
+// Cells of a buffer shared between the workers. The dependency tracking guarantees that no cell is read while it's written,
+// which is what makes handing out slices of it sound.
+struct SharedCells<'a, T> {
+    ptr: *mut T,
+    len: usize,
+    _phantom: PhantomData<&'a mut [T]>,
+}
+
+unsafe impl<T: Send> Send for SharedCells<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for SharedCells<'_, T> {}
+
+impl<'a, T> SharedCells<'a, T> {
+    fn new(cells: &'a mut [T]) -> Self {
+        Self { ptr: cells.as_mut_ptr(), len: cells.len(), _phantom: PhantomData }
+    }
+
+    // SAFETY: no other thread may write the range while the slice is alive
+    unsafe fn range(&self, range: Range<usize>) -> &[T] {
+        assert!(range.start <= range.end && range.end <= self.len);
+        std::slice::from_raw_parts(self.ptr.add(range.start), range.len())
+    }
+
+    // SAFETY: no other thread may read or write the range while the slice is alive
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn range_mut(&self, range: Range<usize>) -> &mut [T] {
+        assert!(range.start <= range.end && range.end <= self.len);
+        std::slice::from_raw_parts_mut(self.ptr.add(range.start), range.len())
+    }
+}
+
+// Runs every generation of a compute without global barriers: region r computes generation g + 1 as soon as r and the regions
+// around it finished generation g. Two buffers are enough, since r overwriting generation g - 1 with g + 1 also waits for
+// its neighbors to be done reading generation g - 1.
+pub struct ExecutorWavefront<T, TConf, TJobber: Jobber<T, TConf>>
+    where T: Copy
+{
+    thread_count: usize,
+    decomposition: WorkDecomposition,
+    cpus: Vec<usize>,
+    main_thread_works: bool,
+    _phantom: PhantomData<(T, TConf, TJobber)>,
+}
+
+impl<T, TConf, TJobber: Jobber<T, TConf>> ExecutorWavefront<T, TConf, TJobber>
+    where T: Copy
+{
+    // Threads are spawned for each compute, which spans the whole run, worker i being pinned like ExecutorParallel's.
+    // Regions are computed from a copy of their surroundings, which the jobber has to support.
+    pub fn new(thread_count: usize, decomposition: WorkDecomposition, cpus: &[usize], main_thread_works: bool) -> Result<Self, ExecutorError> {
+        if !TJobber::supports_region_generations() {
+            return Err(ExecutorError::Unsupported(format!("{} doesn't support wavefront scheduling", std::any::type_name::<TJobber>())));
+        }
+        Ok(Self {
+            thread_count: usize::max(thread_count, 1),
+            decomposition,
+            cpus: cpus.to_vec(),
+            main_thread_works,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+struct Wavefront<'a, T, TConf> {
+    regions: Vec<JobRegion>,
+    // Regions are their own dependents, and dependencies are symmetric
+    dependencies: Vec<Vec<usize>>,
+    // Generations each region finished, and the generation each region was last queued for
+    done: Vec<AtomicUsize>,
+    queued: Vec<AtomicUsize>,
+    finished_regions: AtomicUsize,
+    ready: SegQueue<(usize, usize)>,
+    // What the first panicking job panicked with, every worker stops once it's set
+    failure: Mutex<Option<PanicPayload>>,
+    // Generation g lives in buffers[g % 2]
+    buffers: [SharedCells<'a, T>; 2],
+    dims: (usize, usize),
+    generations: usize,
+    deadline: Option<Instant>,
+    conf: &'a TConf,
+}
+
+impl<T, TConf> Wavefront<'_, T, TConf>
+    where T: Copy
+{
+    // Queues the region's next generation if its dependencies reached the current one and it isn't already queued
+    fn try_queue(&self, region_index: usize) {
+        let done = self.done[region_index].load(Ordering::SeqCst);
+        if done >= self.generations || self.queued[region_index].load(Ordering::SeqCst) != done {
+            return;
+        }
+        let ready = self.dependencies[region_index].iter().all(|dependency| self.done[*dependency].load(Ordering::SeqCst) >= done);
+        if ready && self.queued[region_index].compare_exchange(done, done + 1, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            self.ready.push((region_index, done + 1));
+        }
+    }
+
+    // Copies the region's bounding box and a one cell halo of the previous generation, and computes the region from it
+    fn run_job<TJobber: Jobber<T, TConf>>(&self, region_index: usize, generation: usize) {
+        let (source, destination) = (&self.buffers[(generation - 1) % 2], &self.buffers[generation % 2]);
+        let region = self.regions[region_index];
+        let (x, y, width, height) = region.bounding_box(self.dims.0);
+        let local_x = x.saturating_sub(1);
+        let local_y = y.saturating_sub(1);
+        let local_width = usize::min(x + width + 1, self.dims.0) - local_x;
+        let local_height = usize::min(y + height + 1, self.dims.1) - local_y;
+
+        let mut local_data = Vec::with_capacity(local_width * local_height);
+        for row in local_y..(local_y + local_height) {
+            let row_offset = local_x + row * self.dims.0;
+            // SAFETY: the halo only covers dependencies, which finished this generation and won't write it again before this job is done
+            local_data.extend_from_slice(unsafe { source.range(row_offset..(row_offset + local_width)) });
+        }
+        let local = Buffer {
+            data: local_data.into_boxed_slice(),
+            dims: (local_width, local_height, 1),
+        };
+        for row in region.rows() {
+            let (row_x, row_y) = (row.start % self.dims.0, row.start / self.dims.0);
+            let local_offset = (row_x - local_x) + (row_y - local_y) * local_width;
+            // SAFETY: regions don't overlap, and the dependents reading this region wait for this job to be done
+            let out_row = unsafe { destination.range_mut(row.clone()) };
+            TJobber::process_span(&local, local_offset..(local_offset + row.len()), self.conf, out_row);
+        }
+
+        self.done[region_index].store(generation, Ordering::SeqCst);
+        if generation == self.generations {
+            self.finished_regions.fetch_add(1, Ordering::SeqCst);
+        }
+        for dependent in &self.dependencies[region_index] {
+            self.try_queue(*dependent);
+        }
+    }
+
+    // A panicking job would leave its dependents waiting forever, so every worker stops instead.
+    // Workers also stop taking jobs when interrupted or past the deadline, leaving the regions at different generations.
+    fn work_loop<TJobber: Jobber<T, TConf>>(&self) {
+        while self.finished_regions.load(Ordering::SeqCst) < self.regions.len() {
+            let is_past_deadline = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if self.failure.lock().unwrap().is_some() || is_interrupted() || is_past_deadline {
+                return;
+            }
+            match self.ready.pop() {
+                Some((region_index, generation)) => {
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| self.run_job::<TJobber>(region_index, generation)));
+                    if let Err(payload) = outcome {
+                        self.failure.lock().unwrap().get_or_insert(payload);
+                        return;
+                    }
+                },
+                None => thread::yield_now(),
+            }
+        }
+    }
+
+    // Brings the regions left behind by an early stop up to the furthest one's generation, returning it.
+    // The region furthest behind always has its dependencies ready, so this makes progress until every region caught up.
+    fn catch_up<TJobber: Jobber<T, TConf>>(&self) -> usize {
+        let target = self.done.iter().map(|done| done.load(Ordering::SeqCst)).max().unwrap_or(0);
+        let mut behind = true;
+        while behind {
+            behind = false;
+            for region_index in 0..self.regions.len() {
+                let done = self.done[region_index].load(Ordering::SeqCst);
+                if done < target {
+                    behind = true;
+                    if self.dependencies[region_index].iter().all(|dependency| self.done[*dependency].load(Ordering::SeqCst) >= done) {
+                        self.run_job::<TJobber>(region_index, done + 1);
+                    }
+                }
+            }
+        }
+        target
+    }
+}
+
+impl<T, TConf, TJobber: Jobber<T, TConf>> Executor<T, TConf> for ExecutorWavefront<T, TConf, TJobber>
+    where
+        T: Copy + Send + Sync,
+        TConf: Clone + Sync,
+{
+    fn compute(&self, in_buffer: Buffer<T>, out_buffer: &mut [T], conf: TConf) -> Result<Buffer<T>, ExecutorError> {
+        Ok(self.compute_generations(in_buffer, out_buffer, conf, 1, None)?.0)
+    }
+
+    // The whole run is pipelined
+    fn max_generations_per_compute(&self) -> usize {
+        usize::MAX
+    }
+
+    fn compute_generations(
+        &self,
+        mut in_buffer: Buffer<T>,
+        out_buffer: &mut [T],
+        conf: TConf,
+        generations: usize,
+        deadline: Option<Instant>,
+    ) -> Result<(Buffer<T>, usize), ExecutorError> {
+        check_buffers(&in_buffer, out_buffer)?;
+        let dims = in_buffer.dims_2d();
+        let regions = self.decomposition.regions(dims);
+        let dependencies = region_dependencies(&regions, dims.0, 1);
+        let region_count = regions.len();
+        let wavefront = Wavefront {
+            regions,
+            dependencies,
+            done: (0..region_count).map(|_| AtomicUsize::new(0)).collect(),
+            queued: (0..region_count).map(|_| AtomicUsize::new(0)).collect(),
+            finished_regions: AtomicUsize::new(if generations == 0 { region_count } else { 0 }),
+            ready: SegQueue::new(),
+            failure: Mutex::new(None),
+            buffers: [SharedCells::new(&mut in_buffer.data), SharedCells::new(out_buffer)],
+            dims,
+            generations,
+            deadline,
+            conf: &conf,
+        };
+        for region_index in 0..region_count {
+            wavefront.try_queue(region_index);
+        }
+
+        let worker_count = if self.main_thread_works { self.thread_count - 1 } else { self.thread_count };
+        let first_cpu = if self.main_thread_works { 1 } else { 0 };
+        thread::scope(|scope| {
+            for i in 0..worker_count {
+                let wavefront = &wavefront;
+                scope.spawn(pinned_worker(&self.cpus, first_cpu + i, move || {
+                    wavefront.work_loop::<TJobber>();
+                }));
+            }
+            if self.main_thread_works {
+                // Kept on the CPU the workers leave to it, like ExecutorParallel's
+                let _pin = self.cpus.first().and_then(|cpu| {
+                    let pin = ScopedPin::pin_current_thread(*cpu);
+                    if pin.is_none() {
+                        eprintln!("Unable to pin main thread to cpu{}", cpu);
+                    }
+                    pin
+                });
+                wavefront.work_loop::<TJobber>();
+            }
+        });
+        let mut computed = generations;
+        if wavefront.failure.lock().unwrap().is_none() && wavefront.finished_regions.load(Ordering::SeqCst) < region_count {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| wavefront.catch_up::<TJobber>()));
+            match outcome {
+                Ok(reached) => computed = reached,
+                Err(payload) => *wavefront.failure.lock().unwrap() = Some(payload),
+            }
+        }
+        if let Some(payload) = wavefront.failure.into_inner().unwrap() {
+            return Err(ExecutorError::from_panic(payload));
+        }
+
+        // An even generation count leaves the result in the input buffer
+        if computed.is_multiple_of(2) {
+            out_buffer.copy_from_slice(&in_buffer.data);
+        }
+        Ok((in_buffer, computed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_cells_see_the_underlying_slice() {
+        let mut cells = [0, 1, 2, 3, 4, 5];
+        let shared = SharedCells::new(&mut cells);
+        // SAFETY: the ranges are only used one at a time on this thread
+        unsafe {
+            assert_eq!(shared.range(1..4), &[1, 2, 3]);
+            shared.range_mut(4..6).copy_from_slice(&[40, 50]);
+            assert_eq!(shared.range(3..6), &[3, 40, 50]);
+            assert!(shared.range(6..6).is_empty());
+        }
+        assert_eq!(cells, [0, 1, 2, 3, 40, 50]);
+    }
+
+    #[test]
+    fn shared_cells_take_disjoint_writes_from_threads() {
+        let mut cells = vec![0_usize; 64];
+        let shared = SharedCells::new(&mut cells);
+        thread::scope(|scope| {
+            for chunk in 0..4 {
+                let shared = &shared;
+                scope.spawn(move || {
+                    let range = (chunk * 16)..((chunk + 1) * 16);
+                    // SAFETY: each thread writes its own 16 cells
+                    let out = unsafe { shared.range_mut(range.clone()) };
+                    for (out_cell, index) in out.iter_mut().zip(range) {
+                        *out_cell = index * 2;
+                    }
+                });
+            }
+        });
+        assert!(cells.iter().enumerate().all(|(index, cell)| *cell == index * 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn shared_cells_reject_out_of_bounds_ranges() {
+        let mut cells = [0; 4];
+        let shared = SharedCells::new(&mut cells);
+        // SAFETY: the range is checked before any access
+        unsafe { shared.range(2..5); }
+    }
+}